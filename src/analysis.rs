@@ -0,0 +1,323 @@
+use crate::game::{
+    GameConfig, GameMove, GameState, GridState, MinesweeperGame, index_to_pos, pos_to_index,
+};
+use crate::solver::{
+    Certainty, ConstraintSolver, ForcedGuessKind, RevealJudgement, Solver, judge_reveal,
+};
+use std::time::Duration;
+
+/// Standard difficulty metrics for a mine layout.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BoardMetrics {
+    /// Minimum number of clicks needed to clear the board, without using flags (3BV).
+    pub bbbv: usize,
+    /// Number of positions revealed by a click in each opening.
+    pub openings: Vec<usize>,
+    /// Number of positions in each island, a connected group of counts away from any opening.
+    pub islands: Vec<usize>,
+    /// Number of guesses the constraint solver needs after the first click, once counted by
+    /// `count_guesses`.
+    pub solver_guesses: Option<usize>,
+}
+
+impl BoardMetrics {
+    pub fn of_game(game: &MinesweeperGame) -> Self {
+        Self::of_layout(game.config(), game.layout())
+    }
+
+    /// Compute the metrics for a layout produced by `initialize_grid`, apart from the solver
+    /// guesses, which take a replay of the whole game.
+    pub fn of_layout(config: GameConfig, grid: &[GridState]) -> Self {
+        // each opening is a connected group of empty positions, which also reveals the counts around it
+        let mut openings = Vec::new();
+        let mut in_opening = vec![false; grid.len()];
+        let mut visited = vec![false; grid.len()];
+        for start in 0..grid.len() {
+            if grid[start] != GridState::Empty || visited[start] {
+                continue;
+            }
+
+            let mut size = 0;
+            let mut queue = vec![start];
+            visited[start] = true;
+            while let Some(i) = queue.pop() {
+                if !in_opening[i] {
+                    in_opening[i] = true;
+                    size += 1;
+                }
+                for n in neighbors(config, i) {
+                    if !in_opening[n] {
+                        in_opening[n] = true;
+                        size += 1;
+                    }
+                    if grid[n] == GridState::Empty && !visited[n] {
+                        visited[n] = true;
+                        queue.push(n);
+                    }
+                }
+            }
+            openings.push(size);
+        }
+
+        // every other safe position needs its own click, and connected ones form islands
+        let needs_click =
+            |i: usize| grid[i] != GridState::Mine && grid[i] != GridState::Empty && !in_opening[i];
+        let mut islands = Vec::new();
+        let mut visited = vec![false; grid.len()];
+        for start in 0..grid.len() {
+            if !needs_click(start) || visited[start] {
+                continue;
+            }
+
+            let mut size = 0;
+            let mut queue = vec![start];
+            visited[start] = true;
+            while let Some(i) = queue.pop() {
+                size += 1;
+                for n in neighbors(config, i) {
+                    if needs_click(n) && !visited[n] {
+                        visited[n] = true;
+                        queue.push(n);
+                    }
+                }
+            }
+            islands.push(size);
+        }
+
+        let bbbv = openings.len() + islands.iter().sum::<usize>();
+
+        BoardMetrics {
+            bbbv,
+            openings,
+            islands,
+            solver_guesses: None,
+        }
+    }
+
+    /// Count the guesses the constraint solver needs on the layout of a game.
+    pub fn count_guesses(game: &MinesweeperGame) -> usize {
+        solver_guesses(game.config(), game.layout())
+    }
+
+    /// 3BV per second, for comparing times between boards.
+    pub fn bbbv_per_second(&self, elapsed: Duration) -> f64 {
+        let secs = elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.bbbv as f64 / secs
+        } else {
+            0.0
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        let summary = format!(
+            "3BV {}, {} openings, {} islands",
+            self.bbbv,
+            self.openings.len(),
+            self.islands.len(),
+        );
+        match self.solver_guesses {
+            Some(guesses) => format!("{summary}, {guesses} guesses"),
+            None => summary,
+        }
+    }
+}
+
+fn neighbors(config: GameConfig, i: usize) -> impl Iterator<Item = usize> {
+    let (x, y) = index_to_pos(i, config.width());
+    let (x, y) = (x as i64, y as i64);
+    (y - 1..=y + 1)
+        .flat_map(move |ny| (x - 1..=x + 1).map(move |nx| (nx, ny)))
+        .filter(move |&(nx, ny)| {
+            (nx, ny) != (x, y)
+                && nx >= 0
+                && ny >= 0
+                && nx < config.width() as i64
+                && ny < config.height() as i64
+        })
+        .map(move |(nx, ny)| pos_to_index(nx as u32, ny as u32, config.width()))
+}
+
+/// Play the layout with the constraint solver, counting the guesses it makes after the first click.
+///
+/// Guesses are always lucky: when the solver would reveal a mine, the nearest safe position is
+/// revealed instead, so the count reflects the board rather than the outcome of each guess.
+fn solver_guesses(config: GameConfig, grid: &[GridState]) -> usize {
+    let mines: Vec<usize> = (0..grid.len())
+        .filter(|&i| grid[i] == GridState::Mine)
+        .collect();
+    let mut game = MinesweeperGame::with_mines(config, &mines);
+    let mut solver = ConstraintSolver::default();
+    let mut guesses = 0;
+
+    while !game.game_over() {
+        let m = solver.next_move(&game);
+        let mut game_move = m.game_move;
+
+        match m.certainty {
+            Certainty::Certain => {}
+            Certainty::Forced(forced) if forced.kind == ForcedGuessKind::Opening => {}
+            _ => guesses += 1,
+        }
+
+        if let GameMove::Reveal(x, y) = game_move
+            && grid[pos_to_index(x, y, config.width())] == GridState::Mine
+        {
+            match nearest_safe(&game, x, y) {
+                Some((sx, sy)) => game_move = GameMove::Reveal(sx, sy),
+                None => break,
+            }
+        }
+
+        let before = (game.revealed_count(), game.flagged_count());
+        game.make_move(game_move);
+        if game_move == GameMove::NoOp
+            || (!game.game_over() && before == (game.revealed_count(), game.flagged_count()))
+        {
+            break;
+        }
+    }
+
+    guesses
+}
+
+/// Closest unrevealed position that is not a mine.
+fn nearest_safe(game: &MinesweeperGame, x: u32, y: u32) -> Option<(u32, u32)> {
+    let mut best = None;
+    let mut best_distance = u32::MAX;
+    for ny in 0..game.height() {
+        for nx in 0..game.width() {
+            let hidden = game.peek_at(nx, ny, false) == GridState::Unrevealed;
+            if hidden && game.peek_at(nx, ny, true) != GridState::Mine {
+                let distance = nx.abs_diff(x).max(ny.abs_diff(y));
+                if distance < best_distance {
+                    best_distance = distance;
+                    best = Some((nx, ny));
+                }
+            }
+        }
+    }
+    best
+}
+
+/// Replay of a finished game, judging every reveal against what could be deduced at the time.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GameReview {
+    /// Reveals of positions that weren't provably safe, made while a safe move was available.
+    pub blunders: Vec<(u32, u32)>,
+    /// Reveals made when no position was provably safe, not counting the first click.
+    pub forced_guesses: Vec<(u32, u32)>,
+    /// Other guesses, in positions too large to be sure whether a safe move was available.
+    pub guesses: Vec<(u32, u32)>,
+    /// Judgement of the reveal that lost the game, if it was lost.
+    pub death: Option<RevealJudgement>,
+}
+
+impl GameReview {
+    pub fn of_game(game: &MinesweeperGame) -> Self {
+        let mut review = GameReview::default();
+        let mut replay =
+            MinesweeperGame::with_givens(game.config(), &game.mine_positions(), game.givens());
+
+        for &m in game.moves() {
+            if let GameMove::Reveal(x, y) = m {
+                let judgement = judge_reveal(&replay, x, y);
+                match judgement {
+                    RevealJudgement::Safe => {}
+                    RevealJudgement::Blunder => review.blunders.push((x, y)),
+                    RevealJudgement::Forced(ForcedGuessKind::Opening) => {}
+                    RevealJudgement::Forced(_) => review.forced_guesses.push((x, y)),
+                    RevealJudgement::Guess => review.guesses.push((x, y)),
+                }
+
+                replay.make_move(m);
+                if replay.state() == GameState::Dead {
+                    review.death = Some(judgement);
+                }
+            } else {
+                replay.make_move(m);
+            }
+        }
+
+        review
+    }
+
+    /// Whether the game was lost to a guess that could have been avoided.
+    pub fn avoidable_death(&self) -> bool {
+        self.death == Some(RevealJudgement::Blunder)
+    }
+
+    pub fn summary(&self) -> String {
+        let mut text = match self.death {
+            Some(RevealJudgement::Blunder) => "Avoidable death, ".to_string(),
+            Some(RevealJudgement::Forced(_)) => "Forced death, ".to_string(),
+            Some(_) => "Died guessing, ".to_string(),
+            None => String::new(),
+        };
+        text += &format!(
+            "{} blunders, {} forced guesses",
+            self.blunders.len(),
+            self.forced_guesses.len()
+        );
+        if !self.guesses.is_empty() {
+            text += &format!(", {} other guesses", self.guesses.len());
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::mine_probabilities;
+
+    #[test]
+    fn metrics_of_fixed_layout() {
+        // . . . _
+        // . * . _
+        // . . . _
+        // the right column opens up the column next to it, and the other five counts around the
+        // mine form one island
+        let config = GameConfig::new(4, 3, 1);
+        let game = MinesweeperGame::with_mines(config, &[pos_to_index(1, 1, 4)]);
+        let metrics = BoardMetrics::of_game(&game);
+        assert_eq!(metrics.openings, vec![6]);
+        assert_eq!(metrics.islands, vec![5]);
+        assert_eq!(metrics.bbbv, 6);
+    }
+
+    #[test]
+    fn metrics_without_openings() {
+        // * 1 1 *
+        let config = GameConfig::new(4, 1, 2);
+        let game = MinesweeperGame::with_mines(config, &[0, 3]);
+        let metrics = BoardMetrics::of_game(&game);
+        assert!(metrics.openings.is_empty());
+        assert_eq!(metrics.islands, vec![2]);
+        assert_eq!(metrics.bbbv, 2);
+    }
+
+    #[test]
+    fn probabilities_of_one_two_one() {
+        // * . *
+        // 1 2 1
+        let config = GameConfig::new(3, 2, 2);
+        let game = MinesweeperGame::with_givens(config, &[0, 2], &[3, 4, 5]);
+        assert_eq!(
+            mine_probabilities(&game),
+            vec![Some(1.0), Some(0.0), Some(1.0), None, None, None]
+        );
+    }
+
+    #[test]
+    fn probabilities_of_fifty_fifty() {
+        // * .
+        // 1 1
+        let config = GameConfig::new(2, 2, 1);
+        let game = MinesweeperGame::with_givens(config, &[0], &[2, 3]);
+        assert_eq!(
+            mine_probabilities(&game),
+            vec![Some(0.5), Some(0.5), None, None]
+        );
+    }
+}
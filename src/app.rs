@@ -1,36 +1,1369 @@
-use crate::game::{GameConfig, GameState, GridState, MinesweeperGame};
-use crate::solver::get_next_move;
-use crate::sprites::{SpriteType, Sprites};
-use egui::{vec2, Align, Direction, Key, Ui, ViewportCommand};
-use std::time::Duration;
+use crate::analysis::{BoardMetrics, GameReview};
+use crate::autoplay::{Autoplay, AutoplaySpeed};
+use crate::board::{
+    BoardWidget, GridAction, GridPress, Heatmap, MINIMAP_SIZE, minimap, minimap_image,
+};
+use crate::game::{
+    GameConfig, GameMove, GameState, GridState, HINT_PENALTY, MinesweeperGame, SAVE_FILE,
+};
+use crate::puzzle::Position;
+use crate::solver::{
+    Certainty, Hint, OpeningPolicy, Solver, SolverKind, SolverMove, contradicted_flags,
+    forced_guess, hint, mine_probabilities,
+};
+use crate::sprites::{Renderer, SpriteType, Sprites};
+use crate::stats::{
+    GameRecord, HighScore, HighScores, SCORES_FILE, STATS_FILE, Statistics, format_date,
+};
+use crate::theme::{CLASSIC, THEMES_DIR, Theme, find_themes};
+use crate::vector::CLASSIC_NUMBER_COLORS;
+use egui::{Align, Color32, Direction, Key, Modifiers, Rect, Ui, ViewportCommand, vec2};
+use std::path::{Path, PathBuf};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// Name of the game, which eframe also uses to find the data folder.
+pub const APP_NAME: &str = "Minesweeper";
+
+/// Zoom levels to choose from, as the size of a sprite pixel in points.
+const ZOOM_LEVELS: [f32; 7] = [1.0, 1.5, 2.0, 2.5, 3.0, 3.5, 4.0];
+const DEFAULT_ZOOM: f32 = 2.0;
+
+/// Largest custom board, in blocks on each side.
+const MAX_CUSTOM_SIZE: u32 = 500;
+
+/// Every key the game responds to, for the shortcut reference.
+const SHORTCUTS: [(&str, &str); 15] = [
+    ("Arrows, hjkl", "Show and move the cursor"),
+    ("Ctrl + arrows", "Move the cursor to the edge"),
+    ("Space, Enter", "Reveal, or chord around a count"),
+    ("F", "Flag at the cursor"),
+    ("R", "New game, or restart the puzzle"),
+    ("?", "Hint"),
+    ("S", "One solver move"),
+    ("A", "Start or pause autoplay"),
+    ("P", "Probability heatmap"),
+    ("U", "Jump to unfinished blocks"),
+    (
+        "Shift + H",
+        "Show the whole board while held, leaving the game unrecorded",
+    ),
+    ("Ctrl +", "Zoom in"),
+    ("Ctrl -", "Zoom out"),
+    ("Ctrl 0", "Default zoom"),
+    ("Ctrl + drag", "Pan a large board"),
+];
+
+/// What's remembered between launches, in eframe's storage. The window position is kept by
+/// eframe itself.
+///
+/// Choices from a list are saved by name, and boards as width, height and mines.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Settings {
+    /// Board of the last game.
+    board: (u32, u32, usize),
+    custom: (u32, u32, usize),
+    theme: String,
+    zoom: f32,
+    solver: String,
+    opening: String,
+    autoplay_speed: String,
+    show_heatmap: bool,
+    heatmap_labels: bool,
+    renderer: String,
+    number_colors: Vec<Color32>,
+    player_name: String,
+}
+
+/// Solver guesses and review of a finished game, worked out on another thread.
+struct Analysis {
+    handle: JoinHandle<(usize, GameReview)>,
+    /// Whether the game is still on the board, so the analysis should be shown.
+    current: bool,
+}
 
-#[derive(Default)]
 pub struct MinesweeperApp {
     sprites: Sprites,
     game: MinesweeperGame,
+    solver_kind: SolverKind,
+    opening: OpeningPolicy,
+    solver: Box<dyn Solver>,
+    last_solver_move: Option<SolverMove>,
+    metrics: Option<BoardMetrics>,
+    review: Option<GameReview>,
+    /// Analysis of a finished game, running in the background.
+    analyzing: Option<Analysis>,
+    /// Whether the game on the board has been sent for analysis.
+    analyzed: bool,
+    /// Whether the finished game on the board has been added to the statistics.
+    recorded: bool,
+    show_heatmap: bool,
+    heatmap_labels: bool,
+    probabilities: Option<Vec<Option<f64>>>,
+    autoplay: Autoplay,
+    hint: Option<Hint>,
+    /// Explanation of the last hint, or why there wasn't one, or how saving or opening a game went.
+    hint_text: Option<String>,
+    /// A flag the counts prove is wrong, pointed out instead of a hint.
+    wrong_flag: Option<(u32, u32)>,
+    /// Puzzle being played, so it can be restarted or exported.
+    puzzle: Option<Position>,
+    /// Puzzle being generated in the background, since larger ones take a few seconds.
+    generating: Option<JoinHandle<Position>>,
+    puzzle_path: String,
+    /// Result of the last puzzle load or export.
+    puzzle_status: Option<String>,
+    press: Option<GridPress>,
+    /// Block the keyboard plays at, shown once a cursor key is pressed.
+    cursor: Option<(u32, u32)>,
+    /// One of `ZOOM_LEVELS`.
+    zoom: f32,
+    /// Block to bring into view on the next frame, and where to put it.
+    scroll_to: Option<((u32, u32), Option<Align>)>,
+    /// Blocks in view in the last frame, in block units.
+    visible: Rect,
+    /// Minimap of the board, with the number of moves and the state it was drawn after.
+    minimap: Option<((usize, GameState), egui::TextureHandle)>,
+    /// Theme folders found at startup.
+    themes: Vec<PathBuf>,
+    /// Why the last theme couldn't be loaded, or which sprites it's missing.
+    theme_status: Option<String>,
+    /// Size chosen in the custom game window.
+    custom: GameConfig,
+    show_custom: bool,
+    show_stats: bool,
+    show_shortcuts: bool,
+    show_colors: bool,
+    /// Every finished game the player played.
+    stats: Statistics,
+    /// Why the statistics or best times couldn't be read or saved.
+    stats_status: Option<String>,
+    scores: HighScores,
+    /// A winning game with a best time, waiting for the player's name.
+    pending_score: Option<GameRecord>,
+    /// Name given with the last best time, offered again for the next.
+    player_name: String,
+    /// Whether to put the keyboard in the name field on the next frame.
+    focus_name: bool,
+    show_scores: bool,
+    /// Board shown in the best times window.
+    scores_config: GameConfig,
+    /// Board shown in the statistics window.
+    stats_config: GameConfig,
+    /// Whether the solver has made a move in this game, or the heatmap or the whole board has
+    /// been shown while playing, which keeps it out of the statistics and best times.
+    assisted: bool,
+}
+
+impl Default for MinesweeperApp {
+    fn default() -> Self {
+        let solver_kind = SolverKind::default();
+        let dir = data_dir();
+        let mut problems = Vec::new();
+        let stats = match Statistics::load(dir.join(STATS_FILE)) {
+            Ok((stats, skipped)) => {
+                problems.extend(skipped.iter().map(|line| format!("Skipped {line}")));
+                stats
+            }
+            Err(err) => {
+                problems.push(err);
+                Statistics::empty(dir.join(STATS_FILE))
+            }
+        };
+        let scores = match HighScores::load(dir.join(SCORES_FILE)) {
+            Ok((scores, skipped)) => {
+                problems.extend(skipped.iter().map(|line| format!("Skipped {line}")));
+                scores
+            }
+            Err(err) => {
+                problems.push(err);
+                HighScores::empty(dir.join(SCORES_FILE))
+            }
+        };
+        let stats_status = match problems.len() {
+            0 | 1 => problems.pop(),
+            n => Some(format!("{}, and {} more", problems[0], n - 1)),
+        };
+        Self {
+            sprites: Sprites::default(),
+            game: MinesweeperGame::default(),
+            solver_kind,
+            opening: OpeningPolicy::default(),
+            solver: solver_kind.create(),
+            last_solver_move: None,
+            metrics: None,
+            review: None,
+            analyzing: None,
+            analyzed: false,
+            recorded: false,
+            show_heatmap: false,
+            heatmap_labels: false,
+            probabilities: None,
+            autoplay: Autoplay::default(),
+            hint: None,
+            hint_text: None,
+            wrong_flag: None,
+            puzzle: None,
+            generating: None,
+            puzzle_path: "puzzle.txt".to_string(),
+            puzzle_status: None,
+            press: None,
+            cursor: None,
+            zoom: DEFAULT_ZOOM,
+            scroll_to: None,
+            visible: Rect::NOTHING,
+            minimap: None,
+            themes: find_themes(),
+            theme_status: None,
+            custom: GameConfig::EXPERT,
+            show_custom: false,
+            show_stats: false,
+            show_shortcuts: false,
+            show_colors: false,
+            stats,
+            stats_status,
+            scores,
+            pending_score: None,
+            player_name: String::new(),
+            focus_name: false,
+            show_scores: false,
+            scores_config: GameConfig::BEGINNER,
+            stats_config: GameConfig::BEGINNER,
+            assisted: false,
+        }
+    }
+}
+
+/// Folder for the statistics and best times: eframe's data folder, next to the saved settings,
+/// or where the game is started if there isn't one.
+fn data_dir() -> PathBuf {
+    eframe::storage_dir(APP_NAME)
+        .filter(|dir| std::fs::create_dir_all(dir).is_ok())
+        .unwrap_or_default()
 }
 
 impl MinesweeperApp {
     /// Called once before the first frame.
-    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
-        Default::default()
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        // Ctrl +/- zoom the board rather than the whole interface
+        cc.egui_ctx.options_mut(|o| o.zoom_with_keyboard = false);
+
+        let mut app = Self::default();
+        if let Some(storage) = cc.storage
+            && let Some(settings) = eframe::get_value(storage, eframe::APP_KEY)
+        {
+            app.restore(settings);
+        }
+        app
+    }
+
+    fn settings(&self) -> Settings {
+        let config = |c: GameConfig| (c.width(), c.height(), c.mines());
+        Settings {
+            board: config(self.game.config()),
+            custom: config(self.custom),
+            theme: self.sprites.theme().name().to_string(),
+            zoom: self.zoom,
+            solver: self.solver_kind.name().to_string(),
+            opening: self.opening.name().to_string(),
+            autoplay_speed: self.autoplay.speed.name().to_string(),
+            show_heatmap: self.show_heatmap,
+            heatmap_labels: self.heatmap_labels,
+            renderer: self.sprites.renderer.name().to_string(),
+            number_colors: self.sprites.vector.number_colors.clone(),
+            player_name: self.player_name.clone(),
+        }
+    }
+
+    /// Apply settings saved by an earlier launch, skipping any that no longer make sense, such
+    /// as a theme that has been removed.
+    fn restore(&mut self, settings: Settings) {
+        let config = |(width, height, mines): (u32, u32, usize)| {
+            let size = 2..=MAX_CUSTOM_SIZE;
+            (size.contains(&width) && size.contains(&height) && mines < (width * height) as usize)
+                .then(|| GameConfig::new(width, height, mines))
+        };
+        if let Some(custom) = config(settings.custom) {
+            self.custom = custom;
+        }
+        if let Some(board) = config(settings.board) {
+            self.new_game(board);
+        }
+
+        if let Some(dir) = self
+            .themes
+            .iter()
+            .find(|dir| {
+                dir.file_name()
+                    .is_some_and(|name| name == settings.theme.as_str())
+            })
+            .cloned()
+        {
+            self.load_theme(Some(&dir));
+        }
+        if ZOOM_LEVELS.contains(&settings.zoom) {
+            self.zoom = settings.zoom;
+        }
+
+        if let Some(kind) = SolverKind::ALL
+            .into_iter()
+            .find(|k| k.name() == settings.solver)
+        {
+            self.set_solver(kind);
+        }
+        if let Some(opening) = OpeningPolicy::ALL
+            .into_iter()
+            .find(|p| p.name() == settings.opening)
+        {
+            self.set_opening(opening);
+        }
+        if let Some(speed) = AutoplaySpeed::ALL
+            .into_iter()
+            .find(|s| s.name() == settings.autoplay_speed)
+        {
+            self.autoplay.speed = speed;
+        }
+        self.show_heatmap = settings.show_heatmap;
+        self.heatmap_labels = settings.heatmap_labels;
+
+        if let Some(renderer) = Renderer::ALL
+            .into_iter()
+            .find(|r| r.name() == settings.renderer)
+        {
+            self.sprites.renderer = renderer;
+        }
+        if !settings.number_colors.is_empty() {
+            self.sprites.vector.number_colors = settings.number_colors;
+        }
+        self.player_name = settings.player_name;
+    }
+
+    /// Switch to the theme in a folder, or back to the classic theme with None.
+    fn load_theme(&mut self, dir: Option<&Path>) {
+        let theme = match dir {
+            Some(dir) => match Theme::load(dir) {
+                Ok(theme) => theme,
+                Err(err) => {
+                    self.theme_status = Some(err);
+                    return;
+                }
+            },
+            None => Theme::classic(),
+        };
+
+        let missing = theme.missing();
+        self.theme_status = (!missing.is_empty()).then(|| {
+            let names: Vec<String> = missing.iter().map(|s| format!("{s:?}")).collect();
+            format!(
+                "{} is missing {}, drawn from the classic theme",
+                theme.name(),
+                names.join(", ")
+            )
+        });
+        self.sprites.set_theme(theme);
+    }
+
+    /// Step through `ZOOM_LEVELS`, staying at the ends.
+    fn zoom_by(&mut self, steps: isize) {
+        let current = ZOOM_LEVELS
+            .iter()
+            .position(|&z| z == self.zoom)
+            .unwrap_or(0);
+        let next = current
+            .saturating_add_signed(steps)
+            .min(ZOOM_LEVELS.len() - 1);
+        self.zoom = ZOOM_LEVELS[next];
+    }
+
+    fn new_game(&mut self, config: GameConfig) {
+        self.start_game(MinesweeperGame::new(config));
+        self.puzzle = None;
+    }
+
+    /// Start the current puzzle again, or a new game without one.
+    fn restart(&mut self) {
+        match self.puzzle.take() {
+            Some(puzzle) => self.play_puzzle(puzzle),
+            None => self.new_game(self.game.config()),
+        }
+    }
+
+    fn start_game(&mut self, game: MinesweeperGame) {
+        self.game = game;
+        self.minimap = None;
+        self.solver.reset();
+        self.last_solver_move = None;
+        self.metrics = None;
+        self.review = None;
+        self.analyzed = false;
+        self.recorded = false;
+        if let Some(analysis) = &mut self.analyzing {
+            analysis.current = false;
+        }
+        self.probabilities = None;
+        self.autoplay.new_game();
+        self.hint = None;
+        self.wrong_flag = None;
+        self.hint_text = None;
+        self.assisted = false;
+        // keep the cursor where it was, as far as the new board allows
+        self.cursor = self
+            .cursor
+            .map(|(x, y)| (x.min(self.game.width() - 1), y.min(self.game.height() - 1)));
+    }
+
+    /// The minimap texture, redrawn when the board has changed.
+    fn minimap_texture(&mut self, ctx: &egui::Context) -> egui::TextureHandle {
+        let key = (self.game.moves().len(), self.game.state());
+        match &mut self.minimap {
+            Some((drawn, texture)) => {
+                if *drawn != key {
+                    texture.set(minimap_image(&self.game), egui::TextureOptions::NEAREST);
+                    *drawn = key;
+                }
+                texture.clone()
+            }
+            None => {
+                let texture = ctx.load_texture(
+                    "minimap",
+                    minimap_image(&self.game),
+                    egui::TextureOptions::NEAREST,
+                );
+                self.minimap = Some((key, texture.clone()));
+                texture
+            }
+        }
+    }
+
+    /// Bring into view the nearest unrevealed block next to a revealed one, outside the current
+    /// view if there is one, or else any unrevealed block.
+    fn jump_to_unfinished(&mut self) {
+        let game = &self.game;
+        let center = self.visible.center();
+        let distance = |&(x, y): &(u32, u32)| {
+            let d = vec2(x as f32 + 0.5, y as f32 + 0.5) - center.to_vec2();
+            d.length_sq()
+        };
+        let visible = |&(x, y): &(u32, u32)| {
+            self.visible
+                .contains(egui::pos2(x as f32 + 0.5, y as f32 + 0.5))
+        };
+
+        let unrevealed: Vec<(u32, u32)> = (0..game.height())
+            .flat_map(|y| (0..game.width()).map(move |x| (x, y)))
+            .filter(|&(x, y)| game.peek_at(x, y, false) == GridState::Unrevealed)
+            .collect();
+        let frontier: Vec<(u32, u32)> = unrevealed
+            .iter()
+            .copied()
+            .filter(|&(x, y)| {
+                game.neighbors(x, y).into_iter().any(|(nx, ny)| {
+                    matches!(
+                        game.peek_at(nx, ny, false),
+                        GridState::Empty | GridState::Count(_)
+                    )
+                })
+            })
+            .collect();
+
+        let nearest = |cells: &[(u32, u32)], outside: bool| {
+            cells
+                .iter()
+                .filter(|cell| !outside || !visible(cell))
+                .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+                .copied()
+        };
+        let target = nearest(&frontier, true)
+            .or_else(|| nearest(&frontier, false))
+            .or_else(|| nearest(&unrevealed, true))
+            .or_else(|| nearest(&unrevealed, false));
+
+        if let Some(cell) = target {
+            self.scroll_to = Some((cell, Some(Align::Center)));
+            if self.cursor.is_some() {
+                self.cursor = Some(cell);
+            }
+        }
+    }
+
+    fn play(&mut self, action: GridAction) {
+        match action {
+            GridAction::Reveal(x, y) => self.game.reveal(x, y),
+            GridAction::Flag(x, y) => self.game.toggle_flag(x, y),
+            GridAction::Chord(x, y) => self.game.chord(x, y),
+        };
+        self.probabilities = None;
+        self.clear_hint();
+    }
+
+    /// Keyboard play: arrows or hjkl move the cursor, jumping to the edge with Ctrl, Space or
+    /// Enter reveal at the cursor or chord around a revealed count, and F toggles a flag.
+    fn cursor_keys(&mut self, ctx: &egui::Context) {
+        let directions = [
+            (Key::ArrowLeft, Key::H, -1, 0),
+            (Key::ArrowRight, Key::L, 1, 0),
+            (Key::ArrowUp, Key::K, 0, -1),
+            (Key::ArrowDown, Key::J, 0, 1),
+        ];
+        let (step, jump) = ctx.input(|i| {
+            let mut step = None;
+            for (arrow, letter, dx, dy) in directions {
+                // Shift+H is taken by showing the whole board
+                if i.key_pressed(arrow) || (i.key_pressed(letter) && !i.modifiers.shift) {
+                    step = Some((dx, dy));
+                }
+            }
+            (step, i.modifiers.command)
+        });
+        let play = ctx.input(|i| i.key_pressed(Key::Space) || i.key_pressed(Key::Enter));
+        let flag = ctx.input(|i| i.key_pressed(Key::F));
+        if step.is_none() && !play && !flag {
+            return;
+        }
+
+        // the first key press only shows the cursor
+        let (w, h) = (self.game.width() as i64, self.game.height() as i64);
+        let Some((x, y)) = self.cursor else {
+            self.cursor = Some((0, 0));
+            return;
+        };
+
+        if let Some((dx, dy)) = step {
+            let (x, y) = if jump {
+                (x as i64 + dx * w, y as i64 + dy * h)
+            } else {
+                (x as i64 + dx, y as i64 + dy)
+            };
+            let cursor = (x.clamp(0, w - 1) as u32, y.clamp(0, h - 1) as u32);
+            self.cursor = Some(cursor);
+            self.scroll_to = Some((cursor, None));
+        }
+
+        if play {
+            let action = match self.game.peek_at(x, y, false) {
+                GridState::Count(_) => GridAction::Chord(x, y),
+                _ => GridAction::Reveal(x, y),
+            };
+            self.play(action);
+        }
+        if flag {
+            self.play(GridAction::Flag(x, y));
+        }
+    }
+
+    fn play_puzzle(&mut self, puzzle: Position) {
+        match puzzle.to_game() {
+            Ok(game) => {
+                self.start_game(game);
+                self.puzzle = Some(puzzle);
+            }
+            Err(err) => self.puzzle_status = Some(err),
+        }
+    }
+
+    /// Start generating a puzzle the size of the current game, unless one is on its way.
+    fn generate_puzzle(&mut self) {
+        if self.generating.is_none() {
+            let config = self.game.config();
+            self.generating = Some(std::thread::spawn(move || {
+                Position::generate(config, &mut rand::rng())
+            }));
+            self.puzzle_status = None;
+        }
+    }
+
+    fn load_puzzle(&mut self) {
+        match Position::load(&self.puzzle_path) {
+            Ok(puzzle) => {
+                self.puzzle_status = None;
+                self.play_puzzle(puzzle);
+            }
+            Err(err) => self.puzzle_status = Some(err),
+        }
+    }
+
+    /// Save the puzzle being played, or else the position the player can see.
+    fn export_puzzle(&mut self) {
+        let position = match &self.puzzle {
+            Some(puzzle) => puzzle.clone(),
+            None => Position::from_game(&self.game),
+        };
+        self.puzzle_status = Some(match position.save(&self.puzzle_path) {
+            Ok(()) => format!("Saved {}", self.puzzle_path),
+            Err(err) => err,
+        });
+    }
+
+    /// Save the game on the board to the data folder, to carry on with later.
+    fn save_game(&mut self) {
+        let path = data_dir().join(SAVE_FILE);
+        self.hint_text = Some(match self.game.save(&path) {
+            Ok(()) => format!("Saved the game to {}", path.display()),
+            Err(err) => err,
+        });
+    }
+
+    /// Carry on with the game last saved. A finished game is shown as it ended, and isn't
+    /// recorded again.
+    fn open_game(&mut self) {
+        let path = data_dir().join(SAVE_FILE);
+        let game = match MinesweeperGame::load(&path) {
+            Ok(game) => game,
+            Err(err) => {
+                self.hint_text = Some(err);
+                return;
+            }
+        };
+        // a game started from a puzzle restarts and exports as that puzzle
+        let puzzle = (!game.givens().is_empty()).then(|| {
+            let start =
+                MinesweeperGame::with_givens(game.config(), &game.mine_positions(), game.givens());
+            Position::from_game(&start)
+        });
+        self.start_game(game);
+        self.puzzle = puzzle;
+        if self.game.game_over() {
+            self.metrics = Some(BoardMetrics::of_game(&self.game));
+            self.recorded = true;
+        }
+        self.hint_text = Some(format!("Opened the game saved in {}", path.display()));
+    }
+
+    /// Point out one position the player can be sure about, at the cost of `HINT_PENALTY`.
+    fn show_hint(&mut self) {
+        // a wrong flag misleads every deduction after it, so point it out first
+        if !self.game.game_over()
+            && let Some(&(x, y)) = contradicted_flags(&self.game).first()
+        {
+            self.hint = None;
+            self.wrong_flag = Some((x, y));
+            self.hint_text = Some(format!(
+                "Flag at ({}, {}) contradicts the counts",
+                x + 1,
+                y + 1
+            ));
+            return;
+        }
+
+        self.hint = hint(&self.game);
+        self.hint_text = Some(match &self.hint {
+            Some(h) => {
+                self.game.use_hint();
+                format!("{} (+{}s)", h.description(), HINT_PENALTY.as_secs())
+            }
+            None if self.game.game_over() => "No hint: the game is over".to_string(),
+            None => match forced_guess(&self.game) {
+                Some(kind) => format!("No certain move: {}", kind.description()),
+                None => "No certain move".to_string(),
+            },
+        });
+    }
+
+    /// Forget the hint once the board has changed.
+    fn clear_hint(&mut self) {
+        self.hint = None;
+        self.wrong_flag = None;
+        self.hint_text = None;
+    }
+
+    /// Work out the metrics of the game that just ended, and add it to the statistics unless it
+    /// was a puzzle or the solver helped play it.
+    fn finish_game(&mut self) {
+        let metrics = BoardMetrics::of_game(&self.game);
+        if self.puzzle.is_none() && !self.assisted {
+            self.record_game(GameRecord::now(
+                self.game.config(),
+                self.game.state() == GameState::Completed,
+                self.game.timer_elapsed(),
+                metrics.bbbv,
+                self.game.hints_used(),
+            ));
+        }
+        self.metrics = Some(metrics);
+        self.recorded = true;
+    }
+
+    /// Count the solver guesses and review the moves of the game that just ended in the
+    /// background, since the solver replay takes seconds on large boards.
+    fn analyze_game(&mut self) {
+        let game = self.game.clone();
+        let handle = std::thread::spawn(move || {
+            (
+                BoardMetrics::count_guesses(&game),
+                GameReview::of_game(&game),
+            )
+        });
+        self.analyzing = Some(Analysis {
+            handle,
+            current: true,
+        });
+        self.analyzed = true;
+    }
+
+    /// Show the analysis once it's ready, if its game is still on the board.
+    fn finish_analysis(&mut self) {
+        let Some(analysis) = self.analyzing.take() else {
+            return;
+        };
+        if !analysis.current {
+            return;
+        }
+        match analysis.handle.join() {
+            Ok((guesses, review)) => {
+                if let Some(metrics) = &mut self.metrics {
+                    metrics.solver_guesses = Some(guesses);
+                }
+                self.review = Some(review);
+            }
+            Err(_) => self.hint_text = Some("Game analysis failed".to_string()),
+        }
+    }
+
+    fn record_game(&mut self, record: GameRecord) {
+        self.stats_status = self.stats.add(record).err();
+
+        // best times are for games won without help
+        if record.won
+            && record.hints == 0
+            && !self.assisted
+            && self.scores.qualifies(record.config, record.time)
+        {
+            self.pending_score = Some(record);
+            self.focus_name = true;
+        }
+    }
+
+    /// Put the waiting best time in its table, under the name the player gave.
+    fn save_score(&mut self) {
+        let Some(record) = self.pending_score.take() else {
+            return;
+        };
+        let name = self.player_name.trim();
+        let score = HighScore {
+            config: record.config,
+            time: record.time,
+            name: if name.is_empty() { "Anonymous" } else { name }.to_string(),
+            date: record.date,
+        };
+        if let Err(err) = self.scores.add(score) {
+            self.stats_status = Some(err);
+        }
+        self.scores_config = record.config;
+        self.show_scores = true;
+    }
+
+    /// Make one move with the current solver.
+    fn solver_step(&mut self) {
+        self.assisted = true;
+        let m = self.solver.next_move(&self.game);
+        self.game.make_move(m.game_move);
+        self.last_solver_move = Some(m);
+        self.probabilities = None;
+        self.clear_hint();
+    }
+
+    /// Whether the solver found nothing to do, even though the game isn't over.
+    fn solver_stuck(&self) -> bool {
+        !self.game.game_over()
+            && self
+                .last_solver_move
+                .is_some_and(|m| m.game_move == GameMove::NoOp)
+    }
+
+    /// Make any solver moves that are due, and move on to the next game after each one ends.
+    fn run_autoplay(&mut self, ctx: &egui::Context) {
+        let now = Instant::now();
+        let speed = self.autoplay.speed;
+
+        if self.game.game_over() || self.solver_stuck() {
+            let won = self.game.state() == GameState::Completed;
+            let finished_at = self.autoplay.record_result(won, self.solver_stuck(), now);
+
+            // leave the finished board up for a moment before starting the next game
+            let elapsed = now.duration_since(finished_at);
+            let pause = speed.game_over_pause();
+            if elapsed >= pause {
+                self.new_game(self.game.config());
+                ctx.request_repaint();
+            } else {
+                ctx.request_repaint_after(pause - elapsed);
+            }
+            return;
+        }
+
+        match speed.interval() {
+            None => {}
+            Some(Duration::ZERO) => {
+                // play as many moves as fit in a frame
+                let deadline = now + Duration::from_millis(10);
+                while !self.game.game_over() && !self.solver_stuck() && Instant::now() < deadline {
+                    self.solver_step();
+                }
+                ctx.request_repaint();
+            }
+            Some(interval) => {
+                if self.autoplay.move_due(interval, now) {
+                    self.solver_step();
+                }
+                ctx.request_repaint_after(interval);
+            }
+        }
+    }
+
+    fn set_solver(&mut self, kind: SolverKind) {
+        if kind != self.solver_kind {
+            self.solver_kind = kind;
+            self.solver = kind.create();
+            self.solver.set_opening(self.opening);
+            self.last_solver_move = None;
+        }
+    }
+
+    fn set_opening(&mut self, opening: OpeningPolicy) {
+        if opening != self.opening {
+            self.opening = opening;
+            self.solver.set_opening(opening);
+        }
+    }
+
+    /// The Game, Options and Help menus.
+    fn menu_bar(&mut self, ui: &mut Ui) {
+        egui::MenuBar::new().ui(ui, |ui| {
+            ui.menu_button("Game", |ui| {
+                let new_game = egui::Button::new("New game").shortcut_text("R");
+                if ui.add(new_game).clicked() {
+                    self.restart();
+                }
+                ui.separator();
+
+                let mut config = self.game.config();
+                for (preset, name) in [
+                    (GameConfig::BEGINNER, "Beginner"),
+                    (GameConfig::INTERMEDIATE, "Intermediate"),
+                    (GameConfig::EXPERT, "Expert"),
+                ] {
+                    let label = format!(
+                        "{name} ({}x{}, {} mines)",
+                        preset.width(),
+                        preset.height(),
+                        preset.mines()
+                    );
+                    ui.radio_value(&mut config, preset, label);
+                }
+                if config != self.game.config() {
+                    self.new_game(config);
+                }
+                if ui.button("Custom...").clicked() {
+                    self.show_custom = true;
+                }
+                ui.separator();
+
+                let file = format!("{}, as set in the puzzle bar", self.puzzle_path);
+                if ui
+                    .button("Play puzzle file")
+                    .on_hover_text(format!("Play the puzzle in {file}"))
+                    .clicked()
+                {
+                    self.load_puzzle();
+                }
+                if ui
+                    .button("Export puzzle")
+                    .on_hover_text(format!(
+                        "Write the puzzle, or the counts revealed on the board, to {file}"
+                    ))
+                    .clicked()
+                {
+                    self.export_puzzle();
+                }
+                if ui.button("Generate puzzle").clicked() {
+                    self.generate_puzzle();
+                }
+                ui.separator();
+
+                let saved = data_dir().join(SAVE_FILE);
+                if ui
+                    .button("Save game")
+                    .on_hover_text(format!(
+                        "Save the game on the board to {}, replacing any game saved before",
+                        saved.display()
+                    ))
+                    .clicked()
+                {
+                    self.save_game();
+                }
+                if ui
+                    .button("Open saved game")
+                    .on_hover_text(format!(
+                        "Carry on with the game saved in {}",
+                        saved.display()
+                    ))
+                    .clicked()
+                {
+                    self.open_game();
+                }
+                ui.separator();
+
+                if ui.button("Statistics...").clicked() {
+                    self.stats_config = self.game.config();
+                    self.show_stats = true;
+                }
+                if ui.button("Best times...").clicked() {
+                    self.scores_config = self.game.config();
+                    self.show_scores = true;
+                }
+            });
+
+            ui.menu_button("Options", |ui| {
+                ui.menu_button("Solver", |ui| {
+                    let mut solver_kind = self.solver_kind;
+                    for kind in SolverKind::ALL {
+                        ui.radio_value(&mut solver_kind, kind, kind.name());
+                    }
+                    self.set_solver(solver_kind);
+                    ui.separator();
+                    let mut opening = self.opening;
+                    for policy in OpeningPolicy::ALL {
+                        ui.radio_value(&mut opening, policy, policy.name());
+                    }
+                    self.set_opening(opening);
+                });
+                if ui
+                    .add(egui::Button::new("Solver move").shortcut_text("S"))
+                    .clicked()
+                {
+                    self.solver_step();
+                }
+                let autoplay = if self.autoplay.running {
+                    "Pause autoplay"
+                } else {
+                    "Autoplay"
+                };
+                if ui
+                    .add(egui::Button::new(autoplay).shortcut_text("A"))
+                    .clicked()
+                {
+                    self.autoplay.toggle();
+                }
+                ui.menu_button("Autoplay speed", |ui| {
+                    for speed in AutoplaySpeed::ALL {
+                        ui.radio_value(&mut self.autoplay.speed, speed, speed.name());
+                    }
+                });
+                if ui
+                    .add(egui::Button::new("Hint").shortcut_text("?"))
+                    .clicked()
+                {
+                    self.show_hint();
+                }
+                ui.checkbox(&mut self.show_heatmap, "Heatmap (P)")
+                    .on_hover_text("Games played with the heatmap aren't recorded");
+                ui.separator();
+
+                ui.menu_button("Zoom", |ui| {
+                    if ui
+                        .add(egui::Button::new("Zoom in").shortcut_text("Ctrl +"))
+                        .clicked()
+                    {
+                        self.zoom_by(1);
+                    }
+                    if ui
+                        .add(egui::Button::new("Zoom out").shortcut_text("Ctrl -"))
+                        .clicked()
+                    {
+                        self.zoom_by(-1);
+                    }
+                    ui.separator();
+                    for zoom in ZOOM_LEVELS {
+                        let label = if zoom == DEFAULT_ZOOM {
+                            format!("{zoom}x (Ctrl 0)")
+                        } else {
+                            format!("{zoom}x")
+                        };
+                        ui.radio_value(&mut self.zoom, zoom, label);
+                    }
+                });
+
+                ui.menu_button("Theme", |ui| {
+                    let current = self.sprites.theme().name().to_string();
+                    let mut selected = None;
+                    if ui.radio(current == CLASSIC, CLASSIC).clicked() {
+                        selected = Some(None);
+                    }
+                    for dir in &self.themes {
+                        let name = dir.file_name().unwrap_or_default().to_string_lossy();
+                        if ui.radio(current == name, name).clicked() {
+                            selected = Some(Some(dir.clone()));
+                        }
+                    }
+                    if let Some(dir) = selected {
+                        self.load_theme(dir.as_deref());
+                    }
+                    if let Some(status) = &self.theme_status {
+                        ui.separator();
+                        ui.label(format!("⚠ {status}"));
+                    }
+                    ui.separator();
+                    ui.weak(format!("Themes are read from the {THEMES_DIR} folder"));
+                });
+
+                ui.menu_button("Style", |ui| {
+                    for renderer in Renderer::ALL {
+                        ui.radio_value(&mut self.sprites.renderer, renderer, renderer.name());
+                    }
+                    ui.separator();
+                    let colors = ui.add_enabled(
+                        self.sprites.renderer == Renderer::Vector,
+                        egui::Button::new("Number colors..."),
+                    );
+                    if colors.clicked() {
+                        self.show_colors = true;
+                    }
+                });
+            });
+
+            ui.menu_button("Help", |ui| {
+                if ui.button("Keyboard shortcuts").clicked() {
+                    self.show_shortcuts = true;
+                }
+            });
+        });
+    }
+
+    /// Windows opened from the menus.
+    fn windows(&mut self, ctx: &egui::Context) {
+        let mut start = None;
+        egui::Window::new("Custom game")
+            .open(&mut self.show_custom)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let (mut width, mut height, mut mines) = (
+                    self.custom.width(),
+                    self.custom.height(),
+                    self.custom.mines(),
+                );
+                egui::Grid::new("custom").show(ui, |ui| {
+                    ui.label("Width");
+                    ui.add(egui::DragValue::new(&mut width).range(2..=MAX_CUSTOM_SIZE));
+                    ui.end_row();
+                    ui.label("Height");
+                    ui.add(egui::DragValue::new(&mut height).range(2..=MAX_CUSTOM_SIZE));
+                    ui.end_row();
+                    // leave at least one safe position, so the game can be won
+                    let most = (width * height - 1) as usize;
+                    ui.label("Mines");
+                    ui.add(egui::DragValue::new(&mut mines).range(1..=most));
+                    ui.end_row();
+                    mines = mines.min(most);
+                });
+                self.custom = GameConfig::new(width, height, mines);
+                if ui.button("Start").clicked() {
+                    start = Some(self.custom);
+                }
+            });
+        if let Some(config) = start {
+            self.show_custom = false;
+            self.new_game(config);
+        }
+
+        egui::Window::new("Statistics")
+            .open(&mut self.show_stats)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let mut configs = self.stats.configs();
+                if !configs.contains(&self.game.config()) {
+                    configs.push(self.game.config());
+                }
+                egui::ComboBox::from_label("Board")
+                    .selected_text(config_name(self.stats_config))
+                    .show_ui(ui, |ui| {
+                        for config in configs {
+                            ui.selectable_value(
+                                &mut self.stats_config,
+                                config,
+                                config_name(config),
+                            );
+                        }
+                    });
+
+                let stats = self.stats.for_config(self.stats_config);
+                let time = |time: Option<Duration>| time.map_or("-".to_string(), format_time);
+                egui::Grid::new("stats").show(ui, |ui| {
+                    ui.label("Played");
+                    ui.label(stats.played.to_string());
+                    ui.end_row();
+                    ui.label("Won");
+                    ui.label(format!("{} ({:.0}%)", stats.won, stats.win_rate() * 100.0));
+                    ui.end_row();
+                    ui.label("Streak");
+                    ui.label(format!(
+                        "{}, best {}",
+                        stats.current_streak, stats.best_streak
+                    ));
+                    ui.end_row();
+                    ui.label("Best time");
+                    ui.label(time(stats.best_time));
+                    ui.end_row();
+                    ui.label("Average time");
+                    ui.label(time(stats.average_time));
+                    ui.end_row();
+                });
+
+                let recent: Vec<&GameRecord> = self
+                    .stats
+                    .records()
+                    .iter()
+                    .rev()
+                    .filter(|r| r.config == self.stats_config)
+                    .take(10)
+                    .collect();
+                if !recent.is_empty() {
+                    ui.separator();
+                    egui::Grid::new("recent").striped(true).show(ui, |ui| {
+                        for record in recent {
+                            ui.label(format_date(record.date));
+                            let result = if record.won { "Won" } else { "Lost" };
+                            match record.hints {
+                                0 => ui.label(result),
+                                1 => ui.label(format!("{result}, 1 hint")),
+                                hints => ui.label(format!("{result}, {hints} hints")),
+                            };
+                            ui.label(format_time(record.time));
+                            ui.label(format!("3BV {}", record.bbbv));
+                            ui.end_row();
+                        }
+                    });
+                }
+
+                ui.separator();
+                if let (Some(metrics), Some(review)) = (&self.metrics, &self.review) {
+                    ui.label(format!("Last game: {}", metrics.summary()));
+                    ui.label(review.summary());
+                }
+                ui.label(format!("Autoplay: {}", self.autoplay.tally()))
+                    .on_hover_text("Games the solver plays aren't recorded");
+                if let Some(status) = &self.stats_status {
+                    ui.label(format!("⚠ {status}"));
+                }
+            });
+
+        if let Some(record) = self.pending_score {
+            let mut save = false;
+            let mut skip = false;
+            egui::Window::new("New best time")
+                .collapsible(false)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    ui.label(format!(
+                        "{} on {}",
+                        format_time(record.time),
+                        config_name(record.config)
+                    ));
+                    let edit = ui.add(
+                        egui::TextEdit::singleline(&mut self.player_name).hint_text("Your name"),
+                    );
+                    if self.focus_name {
+                        edit.request_focus();
+                        self.focus_name = false;
+                    }
+                    let entered = edit.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter));
+                    ui.horizontal(|ui| {
+                        save = ui.button("Save").clicked() || entered;
+                        skip = ui.button("Skip").clicked();
+                    });
+                });
+            if save {
+                self.save_score();
+            } else if skip {
+                self.pending_score = None;
+            }
+        }
+
+        egui::Window::new("Best times")
+            .open(&mut self.show_scores)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let mut configs = self.scores.configs();
+                if !configs.contains(&self.game.config()) {
+                    configs.push(self.game.config());
+                }
+                egui::ComboBox::from_label("Board")
+                    .selected_text(config_name(self.scores_config))
+                    .show_ui(ui, |ui| {
+                        for config in configs {
+                            ui.selectable_value(
+                                &mut self.scores_config,
+                                config,
+                                config_name(config),
+                            );
+                        }
+                    });
+
+                let table = self.scores.table(self.scores_config);
+                if table.is_empty() {
+                    ui.label("No wins yet");
+                }
+                egui::Grid::new("scores").striped(true).show(ui, |ui| {
+                    for (rank, score) in table.into_iter().enumerate() {
+                        ui.label(format!("{}.", rank + 1));
+                        ui.label(&score.name);
+                        ui.label(format_time(score.time));
+                        ui.label(format_date(score.date));
+                        ui.end_row();
+                    }
+                });
+            });
+
+        egui::Window::new("Number colors")
+            .open(&mut self.show_colors)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let colors = &mut self.sprites.vector.number_colors;
+                for (n, color) in colors.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.color_edit_button_srgba(color);
+                        ui.label(format!("{}", n + 1));
+                    });
+                }
+                if ui.button("Reset").clicked() {
+                    *colors = CLASSIC_NUMBER_COLORS.to_vec();
+                }
+            });
+
+        egui::Window::new("Keyboard shortcuts")
+            .open(&mut self.show_shortcuts)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("shortcuts").striped(true).show(ui, |ui| {
+                    for (keys, action) in SHORTCUTS {
+                        ui.strong(keys);
+                        ui.label(action);
+                        ui.end_row();
+                    }
+                });
+            });
     }
 }
 
 impl eframe::App for MinesweeperApp {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, eframe::APP_KEY, &self.settings());
+    }
+
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        let top_height = 42.0;
-        let bottom_height = 42.0;
+        // draw sprite pixels at a whole number of screen pixels, so blocks line up
+        let ppp = ctx.pixels_per_point();
+        let zoom = (self.zoom * ppp).round().max(1.0) / ppp;
+        let counter_zoom = self.zoom * 0.75;
+        let top_height = (26.0 * counter_zoom + 3.0).round();
+        let solver_height = 24.0;
+        let autoplay_height = 24.0;
+        let puzzle_height = 24.0;
+
+        // leave the keys alone while typing in a text field
+        let shortcuts = !ctx.wants_keyboard_input();
 
         // every frame, run solver for one move if S key is pressed
-        let run_solver = ctx.input(|i| i.key_pressed(Key::S));
+        let run_solver = shortcuts && ctx.input(|i| i.key_pressed(Key::S));
         if run_solver {
-            let m = get_next_move(&self.game);
-            self.game.make_move(m);
+            self.solver_step();
+        }
+
+        // start or pause autoplay with the A key
+        if shortcuts && ctx.input(|i| i.key_pressed(Key::A)) {
+            self.autoplay.toggle();
+        }
+        if self.autoplay.running {
+            self.run_autoplay(ctx);
+        }
+
+        // ask for a hint with the ? key, since H moves the cursor
+        if shortcuts && ctx.input(|i| i.key_pressed(Key::Questionmark)) {
+            self.show_hint();
+        }
+
+        // zoom the board with Ctrl +/-, back to the default with Ctrl 0
+        if shortcuts {
+            let (zoom_in, zoom_out, zoom_reset) = ctx.input_mut(|i| {
+                (
+                    i.consume_key(Modifiers::COMMAND, Key::Plus)
+                        || i.consume_key(Modifiers::COMMAND, Key::Equals),
+                    i.consume_key(Modifiers::COMMAND, Key::Minus),
+                    i.consume_key(Modifiers::COMMAND, Key::Num0),
+                )
+            });
+            if zoom_in {
+                self.zoom_by(1);
+            }
+            if zoom_out {
+                self.zoom_by(-1);
+            }
+            if zoom_reset {
+                self.zoom = DEFAULT_ZOOM;
+            }
+        }
+
+        // move the board cursor and play at it, unless a button has keyboard focus
+        if shortcuts && ctx.memory(|m| m.focused().is_none()) {
+            self.cursor_keys(ctx);
+        }
+
+        // bring unfinished blocks into view with the U key
+        if shortcuts && ctx.input(|i| i.key_pressed(Key::U)) {
+            self.jump_to_unfinished();
+        }
+
+        // toggle the probability heatmap with the P key
+        if shortcuts && ctx.input(|i| i.key_pressed(Key::P)) {
+            self.show_heatmap = !self.show_heatmap;
+        }
+        if self.show_heatmap && !self.game.game_over() && self.probabilities.is_none() {
+            self.probabilities = Some(mine_probabilities(&self.game));
+        }
+
+        // play a generated puzzle once it's ready
+        if let Some(generating) = &self.generating {
+            if generating.is_finished() {
+                let generating = self.generating.take().unwrap();
+                match generating.join() {
+                    Ok(puzzle) => self.play_puzzle(puzzle),
+                    Err(_) => self.puzzle_status = Some("Puzzle generation failed".to_string()),
+                }
+            } else {
+                ctx.request_repaint_after(Duration::from_millis(100));
+            }
         }
 
+        // once the game is over, record it straight away, and analyze the board it was played on
+        // in the background, one game at a time
+        if self.game.game_over() && !self.recorded {
+            self.finish_game();
+        }
+        if let Some(analysis) = &self.analyzing {
+            if analysis.handle.is_finished() {
+                self.finish_analysis();
+            } else {
+                ctx.request_repaint_after(Duration::from_millis(100));
+            }
+        }
+        if self.game.game_over() && !self.analyzed && self.analyzing.is_none() {
+            self.analyze_game();
+        }
+
+        // menu bar, above everything else
+        let mut panels_height = 0.0;
+        panels_height += egui::TopBottomPanel::top("menu")
+            .show(ctx, |ui| self.menu_bar(ui))
+            .response
+            .rect
+            .height();
+
         // top panel, with numbers and faces
-        egui::TopBottomPanel::top("top")
+        panels_height += egui::TopBottomPanel::top("top")
             .exact_height(top_height)
             .show_separator_line(false)
             .show(ctx, |ui| {
@@ -38,69 +1371,340 @@ impl eframe::App for MinesweeperApp {
 
                 ui.columns(3, |columns| {
                     columns[0].with_layout(egui::Layout::left_to_right(Align::Center), |ui| {
-                        let mines_remaining =
-                            self.game.mines_remaining().max(0).try_into().unwrap();
-                        self.sprites
-                            .digits(ui, mines_remaining, Direction::LeftToRight, 1.5);
+                        // wide enough for every mine, going negative when over-flagged
+                        let mines = self.game.config().mines();
+                        let width = (mines.checked_ilog10().unwrap_or(0) + 1).max(3);
+                        self.sprites.digits(
+                            ui,
+                            self.game.mines_remaining() as i64,
+                            width,
+                            Direction::LeftToRight,
+                            counter_zoom,
+                        );
                     });
 
                     columns[1].with_layout(
                         egui::Layout::centered_and_justified(Direction::LeftToRight),
                         |ui| {
-                            let face = sprite_for_game_state(self.game.state());
-                            let reset = self.sprites.button(ui, face, 1.5).clicked();
-                            let reset = reset || ctx.input(|i| i.key_pressed(Key::R));
+                            let face = match self.press {
+                                Some(GridPress::Reveal(..) | GridPress::Chord) => {
+                                    SpriteType::FaceRuhRoh
+                                }
+                                Some(GridPress::Flag(..)) | None => {
+                                    sprite_for_game_state(self.game.state())
+                                }
+                            };
+                            let button = self.sprites.button(ui, face, counter_zoom);
+                            if button.is_pointer_button_down_on() {
+                                // draw over it while held, since the sprite was already chosen
+                                self.sprites
+                                    .paint(ui, SpriteType::FaceSmileyDown, button.rect);
+                            }
+                            let reset = button.clicked();
+                            let reset =
+                                reset || (shortcuts && ctx.input(|i| i.key_pressed(Key::R)));
                             if reset {
-                                self.game = MinesweeperGame::new(self.game.config());
+                                self.restart();
                             }
                         },
                     );
 
                     columns[2].with_layout(egui::Layout::right_to_left(Align::Center), |ui| {
-                        let timer_secs = self.game.timer_elapsed().as_secs().try_into().unwrap();
-                        self.sprites
-                            .digits(ui, timer_secs, Direction::RightToLeft, 1.5);
+                        // stops at 999, like the classic game
+                        let timer_secs = self.game.timer_elapsed().as_secs().min(999) as i64;
+                        self.sprites.digits(
+                            ui,
+                            timer_secs,
+                            3,
+                            Direction::RightToLeft,
+                            counter_zoom,
+                        );
                     });
                 });
-            });
+            })
+            .response
+            .rect
+            .height();
 
-        // central panel, with minesweeper grid
-        egui::CentralPanel::default().show(ctx, |ui| {
-            let hint = ctx.input(|i| i.key_down(Key::H) && i.modifiers.shift_only());
-            let clicked_pos = minesweeper_grid(ui, &self.sprites, &self.game, hint);
+        // solver panel, at the bottom, to choose the solver used by the S key
+        panels_height += egui::TopBottomPanel::bottom("solver")
+            .exact_height(solver_height)
+            .show_separator_line(false)
+            .show(ctx, |ui| {
+                let mut solver_kind = self.solver_kind;
+                let mut opening = self.opening;
 
-            if let Some((x, y, right)) = clicked_pos {
-                if right {
-                    self.game.toggle_flag(x, y);
-                } else {
-                    self.game.reveal(x, y);
-                }
-            }
-        });
+                ui.with_layout(egui::Layout::left_to_right(Align::Center), |ui| {
+                    ui.label("Solver:");
+                    egui::ComboBox::from_id_salt("solver")
+                        .selected_text(solver_kind.name())
+                        .show_ui(ui, |ui| {
+                            for kind in SolverKind::ALL {
+                                ui.selectable_value(&mut solver_kind, kind, kind.name());
+                            }
+                        });
+                    egui::ComboBox::from_id_salt("opening")
+                        .selected_text(opening.name())
+                        .show_ui(ui, |ui| {
+                            for policy in OpeningPolicy::ALL {
+                                ui.selectable_value(&mut opening, policy, policy.name());
+                            }
+                        })
+                        .response
+                        .on_hover_text("Where the solver makes its first click");
+
+                    ui.checkbox(&mut self.show_heatmap, "Heatmap")
+                        .on_hover_text(
+                            "Tint unrevealed positions by their chance of a mine (P). Games \
+                             played with it aren't recorded",
+                        );
+                    if self.show_heatmap {
+                        ui.checkbox(&mut self.heatmap_labels, "%");
+                    }
+
+                    if ui
+                        .button("Hint")
+                        .on_hover_text(format!(
+                            "Show a certain move, adding {}s to the clock (?)",
+                            HINT_PENALTY.as_secs()
+                        ))
+                        .clicked()
+                    {
+                        self.show_hint();
+                    }
+
+                    if let (Some(metrics), Some(review)) = (&self.metrics, &self.review) {
+                        let mut text = format!("{}; {}", review.summary(), metrics.summary());
+                        if self.game.hints_used() > 0 {
+                            text = format!("{} hints, {}", self.game.hints_used(), text);
+                        }
+                        if self.game.state() == GameState::Completed {
+                            let elapsed = self.game.timer_elapsed();
+                            text =
+                                format!("{:.2} 3BV/s, {}", metrics.bbbv_per_second(elapsed), text);
+                        }
+                        ui.add(egui::Label::new(&text).truncate())
+                            .on_hover_text(&text);
+                    } else if self.analyzing.as_ref().is_some_and(|a| a.current) {
+                        ui.spinner().on_hover_text("Analyzing the game");
+                    } else if let Some(text) = &self.hint_text {
+                        ui.add(egui::Label::new(text).truncate())
+                            .on_hover_text(text);
+                    } else if let Some(m) = self.last_solver_move {
+                        ui.label(solver_move_description(m));
+                    }
+                });
+
+                self.set_solver(solver_kind);
+                self.set_opening(opening);
+            })
+            .response
+            .rect
+            .height();
 
-        // bottom panel, with options to change game size
-        egui::TopBottomPanel::bottom("bottom")
-            .exact_height(bottom_height)
+        // autoplay panel, above the solver panel, to let the solver play continuously
+        panels_height += egui::TopBottomPanel::bottom("autoplay")
+            .exact_height(autoplay_height)
             .show_separator_line(false)
             .show(ctx, |ui| {
-                let mut config = self.game.config();
+                ui.with_layout(egui::Layout::left_to_right(Align::Center), |ui| {
+                    let label = if self.autoplay.running {
+                        "Pause"
+                    } else {
+                        "Play"
+                    };
+                    if ui.button(label).on_hover_text("Autoplay (A)").clicked() {
+                        self.autoplay.toggle();
+                    }
+                    if ui
+                        .button("Step")
+                        .on_hover_text("One solver move (S)")
+                        .clicked()
+                    {
+                        self.solver_step();
+                    }
+
+                    egui::ComboBox::from_id_salt("autoplay_speed")
+                        .selected_text(self.autoplay.speed.name())
+                        .show_ui(ui, |ui| {
+                            for speed in AutoplaySpeed::ALL {
+                                ui.selectable_value(&mut self.autoplay.speed, speed, speed.name());
+                            }
+                        });
 
+                    let tally = self.autoplay.tally();
+                    if ui
+                        .add(
+                            egui::Label::new(&tally)
+                                .truncate()
+                                .sense(egui::Sense::click()),
+                        )
+                        .on_hover_text("Click to reset")
+                        .clicked()
+                    {
+                        self.autoplay.reset_tally();
+                    }
+                });
+            })
+            .response
+            .rect
+            .height();
+
+        // puzzle panel, above the autoplay panel, to generate, load and export puzzles
+        panels_height += egui::TopBottomPanel::bottom("puzzle")
+            .exact_height(puzzle_height)
+            .show_separator_line(false)
+            .show(ctx, |ui| {
                 ui.with_layout(egui::Layout::left_to_right(Align::Center), |ui| {
-                    ui.radio_value(&mut config, GameConfig::BEGINNER, "Beginner");
-                    ui.radio_value(&mut config, GameConfig::INTERMEDIATE, "Intermediate");
-                    ui.radio_value(&mut config, GameConfig::EXPERT, "Expert");
+                    ui.label("Puzzle:");
+                    if self.generating.is_some() {
+                        ui.spinner();
+                    } else if ui
+                        .button("New")
+                        .on_hover_text("Generate a puzzle with a minimal set of given counts")
+                        .clicked()
+                    {
+                        self.generate_puzzle();
+                    }
+
+                    ui.add(egui::TextEdit::singleline(&mut self.puzzle_path).desired_width(120.0));
+                    if ui.button("Load").clicked() {
+                        self.load_puzzle();
+                    }
+                    if ui
+                        .button("Export")
+                        .on_hover_text("Save the puzzle, or the position on the board")
+                        .clicked()
+                    {
+                        self.export_puzzle();
+                    }
+
+                    if let Some(status) = &self.puzzle_status {
+                        ui.add(egui::Label::new(status).truncate())
+                            .on_hover_text(status);
+                    } else if self.puzzle.is_some() {
+                        ui.label(format!("{} given", self.game.givens().len()));
+                    }
                 });
+            })
+            .response
+            .rect
+            .height();
 
-                if config != self.game.config() {
-                    self.game = MinesweeperGame::new(config);
+        // central panel last, so it gets the space the other panels leave
+        let mut grid_size = vec2(0.0, 0.0);
+        egui::CentralPanel::default().show(ctx, |ui| {
+            let show_all =
+                shortcuts && ctx.input(|i| i.key_down(Key::H) && i.modifiers.shift_only());
+            let heatmap = match &self.probabilities {
+                Some(probabilities) if self.show_heatmap && !self.game.game_over() => {
+                    Some(Heatmap {
+                        probabilities,
+                        labels: self.heatmap_labels,
+                    })
                 }
-            });
+                _ => None,
+            };
+            if (show_all || heatmap.is_some()) && !self.game.game_over() {
+                self.assisted = true;
+            }
+            let mut marks = Vec::new();
+            if let Some(review) = &self.review {
+                let blunder = Color32::from_rgb(255, 128, 0);
+                marks.extend(review.blunders.iter().map(|&(x, y)| (x, y, blunder)));
+            }
+            if let Some(h) = self.hint {
+                let color = if h.mine {
+                    Color32::from_rgb(220, 0, 0)
+                } else {
+                    Color32::from_rgb(0, 180, 0)
+                };
+                marks.push((h.x, h.y, color));
+            }
+            if let Some((x, y)) = self.wrong_flag {
+                marks.push((x, y, Color32::from_rgb(200, 0, 200)));
+            }
+            if let Some((x, y)) = self.cursor {
+                marks.push((x, y, Color32::from_rgb(0, 90, 255)));
+            }
+            let press = self.press;
+            let scroll_to = self.scroll_to.take();
+            let scroll = egui::ScrollArea::both()
+                // dragging presses blocks, so pan with Ctrl instead
+                .scroll_source(egui::scroll_area::ScrollSource {
+                    drag: false,
+                    ..Default::default()
+                })
+                .show_viewport(ui, |ui, viewport| {
+                    ui.spacing_mut().item_spacing = vec2(0.0, 0.0);
+                    BoardWidget {
+                        sprites: &self.sprites,
+                        game: &self.game,
+                        press: &mut self.press,
+                        zoom,
+                        show_all,
+                        heatmap,
+                        marks: &marks,
+                        viewport,
+                        scroll_to,
+                    }
+                    .show(ui)
+                });
+            let action = scroll.inner;
+            let block = 16.0 * zoom;
+            grid_size = vec2(self.game.width() as f32, self.game.height() as f32) * block;
+            self.visible = Rect::from_min_size(
+                (scroll.state.offset / block).to_pos2(),
+                scroll.inner_rect.size() / block,
+            );
+
+            // the minimap, once the board doesn't fit
+            if scroll.content_size.x > scroll.inner_rect.width() + 1.0
+                || scroll.content_size.y > scroll.inner_rect.height() + 1.0
+            {
+                let texture = self.minimap_texture(ctx);
+                egui::Area::new(egui::Id::new("minimap"))
+                    .fixed_pos(scroll.inner_rect.right_top() + vec2(-MINIMAP_SIZE - 24.0, 8.0))
+                    .show(ctx, |ui| {
+                        egui::Frame::popup(ui.style()).show(ui, |ui| {
+                            if let Some(cell) = minimap(ui, &texture, &self.game, self.visible) {
+                                self.scroll_to = Some((cell, Some(Align::Center)));
+                            }
+                            if ui
+                                .button("Jump to unfinished")
+                                .on_hover_text("Bring unfinished blocks into view (U)")
+                                .clicked()
+                            {
+                                self.jump_to_unfinished();
+                            }
+                        });
+                    });
+            }
+            // the face is drawn before the grid, so show it again with the new press
+            if self.press != press {
+                ctx.request_repaint();
+            }
+
+            if let Some(action) = action {
+                // the cursor follows the mouse once the keyboard has shown it
+                if self.cursor.is_some() {
+                    self.cursor = Some(action.position());
+                }
+                self.play(action);
+            }
+        });
+
+        self.windows(ctx);
 
-        // resize window to match contents
-        let window_size = vec2(
-            32. * self.game.width() as f32 + 10.,
-            32. * self.game.height() as f32 + 10. + top_height + bottom_height,
-        );
+        // resize window to match contents, measured in points
+        let margins = egui::Frame::central_panel(&ctx.style())
+            .total_margin()
+            .sum();
+        let mut window_size = grid_size + margins + vec2(0.0, panels_height);
+        // boards too big for the screen scroll instead
+        if let Some(monitor) = ctx.input(|i| i.viewport().monitor_size) {
+            window_size = window_size.min(monitor * 0.9);
+        }
         ctx.send_viewport_cmd(ViewportCommand::InnerSize(window_size));
 
         // ensure the timer increments while playing, even if no user interaction
@@ -110,40 +1714,36 @@ impl eframe::App for MinesweeperApp {
     }
 }
 
-/// Draw minesweeper grid.
-///
-/// Uses sprites to draw each block.
-///
-/// Return the grid position of a user click, or None.
-fn minesweeper_grid(
-    ui: &mut Ui,
-    sprites: &Sprites,
-    game: &MinesweeperGame,
-    show_all: bool,
-) -> Option<(u32, u32, bool)> {
-    let mut result = None;
-
-    ui.spacing_mut().item_spacing = vec2(0.0, 0.0);
-
-    ui.vertical(|ui| {
-        for y in 0..game.height() {
-            ui.horizontal(|ui| {
-                for x in 0..game.width() {
-                    let state = game.peek_at(x, y, show_all);
-                    let sprite = sprite_for_grid(state);
-                    let btn = sprites.button(ui, sprite, 2.0);
-                    let clicked = btn.clicked();
-                    let right_clicked = btn.secondary_clicked();
-
-                    if clicked || right_clicked {
-                        result = Some((x, y, right_clicked))
-                    }
-                }
-            });
-        }
-    });
+fn solver_move_description(m: SolverMove) -> String {
+    match (m.game_move, m.certainty) {
+        (GameMove::NoOp, _) => "No move found".to_string(),
+        (_, Certainty::Certain) => "Certain move".to_string(),
+        (_, Certainty::Guess) => "Guess".to_string(),
+        (_, Certainty::Forced(forced)) => format!(
+            "Forced: {} ({:.0}% mine)",
+            forced.kind.description(),
+            forced.mine_probability * 100.0
+        ),
+    }
+}
 
-    result
+/// The name of a classic difficulty, or the size of a custom board.
+fn config_name(config: GameConfig) -> String {
+    match config {
+        GameConfig::BEGINNER => "Beginner".to_string(),
+        GameConfig::INTERMEDIATE => "Intermediate".to_string(),
+        GameConfig::EXPERT => "Expert".to_string(),
+        _ => format!(
+            "{}x{}, {} mines",
+            config.width(),
+            config.height(),
+            config.mines()
+        ),
+    }
+}
+
+fn format_time(time: Duration) -> String {
+    format!("{:.3}s", time.as_secs_f64())
 }
 
 fn sprite_for_game_state(state: GameState) -> SpriteType {
@@ -153,15 +1753,3 @@ fn sprite_for_game_state(state: GameState) -> SpriteType {
         GameState::Dead => SpriteType::FaceXXX,
     }
 }
-
-fn sprite_for_grid(state: GridState) -> SpriteType {
-    match state {
-        GridState::Empty => SpriteType::BlockEmptyDown,
-        GridState::Count(count) => SpriteType::block_digit(count.into()),
-        GridState::Mine => SpriteType::BlockMine,
-        GridState::Unrevealed => SpriteType::BlockEmptyUp,
-        GridState::Flagged => SpriteType::BlockFlag,
-        GridState::MineHighlighted => SpriteType::BlockMineRed,
-        GridState::MineIncorrect => SpriteType::BlockMineX,
-    }
-}
@@ -0,0 +1,118 @@
+use std::time::{Duration, Instant};
+
+/// How quickly the solver plays by itself.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum AutoplaySpeed {
+    /// Only move when asked to step.
+    Step,
+    #[default]
+    Slow,
+    Fast,
+    /// As many moves as fit in each frame.
+    Instant,
+}
+
+impl AutoplaySpeed {
+    pub const ALL: [AutoplaySpeed; 4] = [
+        AutoplaySpeed::Step,
+        AutoplaySpeed::Slow,
+        AutoplaySpeed::Fast,
+        AutoplaySpeed::Instant,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AutoplaySpeed::Step => "Step",
+            AutoplaySpeed::Slow => "Slow",
+            AutoplaySpeed::Fast => "Fast",
+            AutoplaySpeed::Instant => "Instant",
+        }
+    }
+
+    /// Time between moves, or None when only stepping.
+    pub fn interval(self) -> Option<Duration> {
+        match self {
+            AutoplaySpeed::Step => None,
+            AutoplaySpeed::Slow => Some(Duration::from_millis(250)),
+            AutoplaySpeed::Fast => Some(Duration::from_millis(25)),
+            AutoplaySpeed::Instant => Some(Duration::ZERO),
+        }
+    }
+
+    /// Time to show a finished game before starting the next one.
+    pub fn game_over_pause(self) -> Duration {
+        match self {
+            AutoplaySpeed::Step | AutoplaySpeed::Slow => Duration::from_millis(1500),
+            AutoplaySpeed::Fast => Duration::from_millis(500),
+            AutoplaySpeed::Instant => Duration::ZERO,
+        }
+    }
+}
+
+/// State of the solver playing by itself, over many games in a row.
+#[derive(Default)]
+pub struct Autoplay {
+    pub running: bool,
+    pub speed: AutoplaySpeed,
+    pub wins: usize,
+    pub losses: usize,
+    /// Games abandoned because the solver had no move to make.
+    pub stuck: usize,
+    last_move: Option<Instant>,
+    game_over_at: Option<Instant>,
+}
+
+impl Autoplay {
+    pub fn toggle(&mut self) {
+        self.running = !self.running;
+        self.last_move = None;
+    }
+
+    pub fn reset_tally(&mut self) {
+        self.wins = 0;
+        self.losses = 0;
+        self.stuck = 0;
+    }
+
+    /// Call when a new game starts, so the next result is counted.
+    pub fn new_game(&mut self) {
+        self.game_over_at = None;
+    }
+
+    /// Count the result of a finished game once, returning when it finished.
+    pub fn record_result(&mut self, won: bool, stuck: bool, now: Instant) -> Instant {
+        *self.game_over_at.get_or_insert_with(|| {
+            if stuck {
+                self.stuck += 1;
+            } else if won {
+                self.wins += 1;
+            } else {
+                self.losses += 1;
+            }
+            now
+        })
+    }
+
+    /// Whether the next move is due, remembering that it was made.
+    pub fn move_due(&mut self, interval: Duration, now: Instant) -> bool {
+        let due = self
+            .last_move
+            .is_none_or(|last| now.duration_since(last) >= interval);
+        if due {
+            self.last_move = Some(now);
+        }
+        due
+    }
+
+    pub fn tally(&self) -> String {
+        let played = self.wins + self.losses + self.stuck;
+        let mut text = format!("Won {}, lost {}", self.wins, self.losses);
+        if self.stuck > 0 {
+            text += &format!(", stuck {}", self.stuck);
+        }
+        if played > 0 {
+            text += &format!(" ({:.0}%)", 100.0 * self.wins as f64 / played as f64);
+        }
+        text
+    }
+}
@@ -0,0 +1,469 @@
+//! Headless benchmark, which plays many seeded games with each solver and reports how well it did.
+//!
+//! Usage: minesweeper-bench [--games N] [--seed S] [--solver NAME|all] [--opening NAME] [--openings] [--config NAME|WxHxM|all] [--format table|csv|json]
+//!
+//! With `--openings`, compares first click positions instead of solvers: each candidate position
+//! plays the same layouts, finished by the constraint solver, reporting how often the first click
+//! opened up the board and how often the game was won.
+
+use minesweeper::game::{GameConfig, GameMove, GameState, MinesweeperGame};
+use minesweeper::solver::{
+    Certainty, OpeningPolicy, OpeningStats, SolverKind, candidate_openings, learn_opening,
+    study_openings,
+};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use std::time::{Duration, Instant};
+
+/// Configs played when none is given.
+const PRESETS: [(&str, GameConfig); 3] = [
+    ("beginner", GameConfig::BEGINNER),
+    ("intermediate", GameConfig::INTERMEDIATE),
+    ("expert", GameConfig::EXPERT),
+];
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Format {
+    Table,
+    Csv,
+    Json,
+}
+
+struct Options {
+    games: usize,
+    seed: u64,
+    solvers: Vec<SolverKind>,
+    opening: OpeningPolicy,
+    /// Compare first click positions rather than solvers.
+    openings: bool,
+    configs: Vec<(String, GameConfig)>,
+    format: Format,
+}
+
+/// Totals over every game played by one solver with one config.
+struct BenchResult {
+    config_name: String,
+    config: GameConfig,
+    solver: SolverKind,
+    games: usize,
+    wins: usize,
+    /// Moves the solver reported as a guess rather than certain, forced or not.
+    guesses: usize,
+    /// Guesses where no safe move existed, as reported by the solver.
+    forced_guesses: usize,
+    moves: usize,
+    /// Games where the solver stopped making moves before the game was over.
+    stuck: usize,
+    solver_time: Duration,
+}
+
+impl BenchResult {
+    fn win_rate(&self) -> f64 {
+        self.wins as f64 / self.games as f64
+    }
+
+    fn avg_guesses(&self) -> f64 {
+        self.guesses as f64 / self.games as f64
+    }
+
+    fn avg_forced_guesses(&self) -> f64 {
+        self.forced_guesses as f64 / self.games as f64
+    }
+
+    fn moves_per_game(&self) -> f64 {
+        self.moves as f64 / self.games as f64
+    }
+
+    fn micros_per_move(&self) -> f64 {
+        if self.moves == 0 {
+            0.0
+        } else {
+            self.solver_time.as_secs_f64() * 1e6 / self.moves as f64
+        }
+    }
+}
+
+fn main() {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("error: {err}");
+            eprintln!(
+                "usage: minesweeper-bench [--games N] [--seed S] [--solver NAME|all] [--opening NAME] [--openings] [--config NAME|WxHxM|all] [--format table|csv|json]"
+            );
+            std::process::exit(2);
+        }
+    };
+
+    if options.openings {
+        let mut results = Vec::new();
+        for (config_name, config) in &options.configs {
+            let positions = candidate_openings(*config);
+            for stats in study_openings(*config, &positions, options.games, options.seed) {
+                results.push((config_name.clone(), *config, stats));
+            }
+        }
+
+        match options.format {
+            Format::Table => print_openings_table(&results),
+            Format::Csv => print_openings_csv(&results),
+            Format::Json => print_openings_json(&results),
+        }
+        return;
+    }
+
+    // learn openings up front, rather than playing the corner while they're learned
+    if options.opening == OpeningPolicy::Learned {
+        for (_, config) in &options.configs {
+            learn_opening(*config);
+        }
+    }
+
+    let mut results = Vec::new();
+    for (config_name, config) in &options.configs {
+        for &solver in &options.solvers {
+            results.push(bench(config_name, *config, solver, &options));
+        }
+    }
+
+    match options.format {
+        Format::Table => print_table(&results),
+        Format::Csv => print_csv(&results),
+        Format::Json => print_json(&results),
+    }
+}
+
+/// Play every game for one config and solver, using the same seeded layouts for each solver.
+fn bench(
+    config_name: &str,
+    config: GameConfig,
+    kind: SolverKind,
+    options: &Options,
+) -> BenchResult {
+    let mut rng = StdRng::seed_from_u64(options.seed);
+    let mut result = BenchResult {
+        config_name: config_name.to_string(),
+        config,
+        solver: kind,
+        games: options.games,
+        wins: 0,
+        guesses: 0,
+        forced_guesses: 0,
+        moves: 0,
+        stuck: 0,
+        solver_time: Duration::ZERO,
+    };
+
+    let mut solver = kind.create();
+    solver.set_opening(options.opening);
+    solver.set_seed(options.seed);
+    for _ in 0..options.games {
+        let mut game = MinesweeperGame::with_rng(config, &mut rng);
+        solver.reset();
+
+        while !game.game_over() {
+            let start = Instant::now();
+            let m = solver.next_move(&game);
+            result.solver_time += start.elapsed();
+
+            match m.certainty {
+                Certainty::Certain => {}
+                Certainty::Guess => result.guesses += 1,
+                Certainty::Forced(_) => {
+                    result.guesses += 1;
+                    result.forced_guesses += 1;
+                }
+            }
+
+            // give up on the game if the solver has nothing to do, or its move changed nothing
+            if m.game_move == GameMove::NoOp {
+                result.stuck += 1;
+                break;
+            }
+            let before = (game.revealed_count(), game.flagged_count());
+            game.make_move(m.game_move);
+            result.moves += 1;
+            if !game.game_over() && before == (game.revealed_count(), game.flagged_count()) {
+                result.stuck += 1;
+                break;
+            }
+        }
+
+        if game.state() == GameState::Completed {
+            result.wins += 1;
+        }
+    }
+
+    result
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        games: 1000,
+        seed: 0,
+        solvers: vec![SolverKind::Constraint],
+        opening: OpeningPolicy::default(),
+        openings: false,
+        configs: PRESETS
+            .iter()
+            .map(|(name, config)| (name.to_string(), *config))
+            .collect(),
+        format: Format::Table,
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("missing value for {arg}"))
+        };
+        match arg.as_str() {
+            "--games" | "-n" => {
+                options.games = value()?.parse().map_err(|_| "invalid number of games")?;
+            }
+            "--seed" => {
+                options.seed = value()?.parse().map_err(|_| "invalid seed")?;
+            }
+            "--solver" => {
+                options.solvers = parse_solvers(&value()?)?;
+            }
+            "--opening" => {
+                let name = value()?;
+                options.opening = OpeningPolicy::ALL
+                    .into_iter()
+                    .find(|policy| policy.name().eq_ignore_ascii_case(&name))
+                    .ok_or_else(|| format!("unknown opening: {name}"))?;
+            }
+            "--openings" => options.openings = true,
+            "--config" => {
+                options.configs = parse_configs(&value()?)?;
+            }
+            "--format" => {
+                options.format = match value()?.as_str() {
+                    "table" => Format::Table,
+                    "csv" => Format::Csv,
+                    "json" => Format::Json,
+                    other => return Err(format!("unknown format: {other}")),
+                };
+            }
+            other => return Err(format!("unknown argument: {other}")),
+        }
+    }
+
+    if options.games == 0 {
+        return Err("must play at least one game".to_string());
+    }
+
+    Ok(options)
+}
+
+fn parse_solvers(value: &str) -> Result<Vec<SolverKind>, String> {
+    if value == "all" {
+        return Ok(SolverKind::ALL.to_vec());
+    }
+
+    value
+        .split(',')
+        .map(|name| {
+            SolverKind::ALL
+                .into_iter()
+                .find(|kind| kind.name().eq_ignore_ascii_case(name))
+                .ok_or_else(|| format!("unknown solver: {name}"))
+        })
+        .collect()
+}
+
+fn parse_configs(value: &str) -> Result<Vec<(String, GameConfig)>, String> {
+    if value == "all" {
+        return Ok(PRESETS
+            .iter()
+            .map(|(name, config)| (name.to_string(), *config))
+            .collect());
+    }
+
+    value
+        .split(',')
+        .map(|name| {
+            if let Some((_, config)) = PRESETS.iter().find(|(preset, _)| *preset == name) {
+                return Ok((name.to_string(), *config));
+            }
+
+            // custom config, given as WIDTHxHEIGHTxMINES
+            let parts: Vec<&str> = name.split('x').collect();
+            let invalid = || format!("invalid config: {name}");
+            if parts.len() != 3 {
+                return Err(invalid());
+            }
+            let width: u32 = parts[0].parse().map_err(|_| invalid())?;
+            let height: u32 = parts[1].parse().map_err(|_| invalid())?;
+            let mines: usize = parts[2].parse().map_err(|_| invalid())?;
+            let cells = width.checked_mul(height).ok_or_else(invalid)?;
+            if width == 0 || height == 0 || mines > cells as usize {
+                return Err(invalid());
+            }
+            Ok((name.to_string(), GameConfig::new(width, height, mines)))
+        })
+        .collect()
+}
+
+fn print_table(results: &[BenchResult]) {
+    println!(
+        "{:<14} {:>10} {:<12} {:>7} {:>8} {:>8} {:>8} {:>8} {:>7} {:>10}",
+        "config",
+        "size",
+        "solver",
+        "games",
+        "win %",
+        "guesses",
+        "forced",
+        "moves",
+        "stuck",
+        "us/move"
+    );
+    for r in results {
+        println!(
+            "{:<14} {:>10} {:<12} {:>7} {:>8.2} {:>8.2} {:>8.2} {:>8.1} {:>7} {:>10.1}",
+            r.config_name,
+            size(r.config),
+            r.solver.name(),
+            r.games,
+            r.win_rate() * 100.0,
+            r.avg_guesses(),
+            r.avg_forced_guesses(),
+            r.moves_per_game(),
+            r.stuck,
+            r.micros_per_move(),
+        );
+    }
+}
+
+fn print_csv(results: &[BenchResult]) {
+    println!(
+        "config,width,height,mines,solver,games,wins,win_rate,avg_guesses,avg_forced_guesses,moves_per_game,stuck,us_per_move"
+    );
+    for r in results {
+        println!(
+            "{},{},{},{},{},{},{},{:.4},{:.4},{:.4},{:.2},{},{:.2}",
+            r.config_name,
+            r.config.width(),
+            r.config.height(),
+            r.config.mines(),
+            r.solver.name(),
+            r.games,
+            r.wins,
+            r.win_rate(),
+            r.avg_guesses(),
+            r.avg_forced_guesses(),
+            r.moves_per_game(),
+            r.stuck,
+            r.micros_per_move(),
+        );
+    }
+}
+
+fn print_json(results: &[BenchResult]) {
+    println!("[");
+    for (i, r) in results.iter().enumerate() {
+        let separator = if i + 1 < results.len() { "," } else { "" };
+        println!(
+            "  {{\"config\": {}, \"width\": {}, \"height\": {}, \"mines\": {}, \"solver\": \"{}\", \"games\": {}, \"wins\": {}, \"win_rate\": {:.4}, \"avg_guesses\": {:.4}, \"avg_forced_guesses\": {:.4}, \"moves_per_game\": {:.2}, \"stuck\": {}, \"us_per_move\": {:.2}}}{}",
+            json_string(&r.config_name),
+            r.config.width(),
+            r.config.height(),
+            r.config.mines(),
+            r.solver.name(),
+            r.games,
+            r.wins,
+            r.win_rate(),
+            r.avg_guesses(),
+            r.avg_forced_guesses(),
+            r.moves_per_game(),
+            r.stuck,
+            r.micros_per_move(),
+            separator,
+        );
+    }
+    println!("]");
+}
+
+/// Results of an opening study, with the name of the config each position was played on.
+type OpeningResult = (String, GameConfig, OpeningStats);
+
+fn print_openings_table(results: &[OpeningResult]) {
+    println!(
+        "{:<14} {:>10} {:>10} {:>7} {:>10} {:>8}",
+        "config", "size", "position", "games", "opening %", "win %"
+    );
+    for (config_name, config, s) in results {
+        println!(
+            "{:<14} {:>10} {:>10} {:>7} {:>10.2} {:>8.2}",
+            config_name,
+            size(*config),
+            format!("{},{}", s.position.0, s.position.1),
+            s.games,
+            s.opening_rate() * 100.0,
+            s.win_rate() * 100.0,
+        );
+    }
+}
+
+fn print_openings_csv(results: &[OpeningResult]) {
+    println!("config,width,height,mines,x,y,games,openings,opening_rate,wins,win_rate");
+    for (config_name, config, s) in results {
+        println!(
+            "{},{},{},{},{},{},{},{},{:.4},{},{:.4}",
+            config_name,
+            config.width(),
+            config.height(),
+            config.mines(),
+            s.position.0,
+            s.position.1,
+            s.games,
+            s.openings,
+            s.opening_rate(),
+            s.wins,
+            s.win_rate(),
+        );
+    }
+}
+
+fn print_openings_json(results: &[OpeningResult]) {
+    println!("[");
+    for (i, (config_name, config, s)) in results.iter().enumerate() {
+        let separator = if i + 1 < results.len() { "," } else { "" };
+        println!(
+            "  {{\"config\": {}, \"width\": {}, \"height\": {}, \"mines\": {}, \"x\": {}, \"y\": {}, \"games\": {}, \"openings\": {}, \"opening_rate\": {:.4}, \"wins\": {}, \"win_rate\": {:.4}}}{}",
+            json_string(config_name),
+            config.width(),
+            config.height(),
+            config.mines(),
+            s.position.0,
+            s.position.1,
+            s.games,
+            s.openings,
+            s.opening_rate(),
+            s.wins,
+            s.win_rate(),
+            separator,
+        );
+    }
+    println!("]");
+}
+
+fn size(config: GameConfig) -> String {
+    format!("{}x{}x{}", config.width(), config.height(), config.mines())
+}
+
+/// A string as a quoted JSON string.
+fn json_string(s: &str) -> String {
+    let mut json = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
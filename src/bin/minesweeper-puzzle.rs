@@ -0,0 +1,83 @@
+//! Check a puzzle position, reporting whether it has a unique solution and each deduction that
+//! leads there.
+//!
+//! Usage: minesweeper-puzzle [--boards] FILE|-
+//!
+//! See `minesweeper::puzzle::Position` for the file format. With `--boards`, the position is
+//! printed again after each deduction.
+
+use minesweeper::puzzle::Position;
+use minesweeper::solver::solve_position;
+use std::io::Read;
+
+struct Options {
+    path: String,
+    boards: bool,
+}
+
+fn main() {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("error: {err}");
+            eprintln!("usage: minesweeper-puzzle [--boards] FILE|-");
+            std::process::exit(2);
+        }
+    };
+
+    let position = match load(&options.path) {
+        Ok(position) => position,
+        Err(err) => {
+            eprintln!("error: {err}");
+            std::process::exit(1);
+        }
+    };
+
+    let solution = solve_position(&position);
+    let mines = match position.mines() {
+        Some(mines) => format!("{mines} mines"),
+        None => "unknown number of mines".to_string(),
+    };
+    println!("{}x{}, {mines}", position.width(), position.height());
+    println!("{}", solution.uniqueness.description());
+
+    let mut board = position.clone();
+    for (n, step) in solution.steps.iter().enumerate() {
+        println!("{}. {}", n + 1, step.description());
+        if options.boards {
+            board.apply(step);
+            println!("{board}");
+        }
+    }
+}
+
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut path = None;
+    let mut boards = false;
+
+    for arg in args {
+        match arg.as_str() {
+            "--boards" => boards = true,
+            _ if path.is_none() && (arg == "-" || !arg.starts_with('-')) => path = Some(arg),
+            other => return Err(format!("unexpected argument: {other}")),
+        }
+    }
+
+    Ok(Options {
+        path: path.ok_or("missing puzzle file")?,
+        boards,
+    })
+}
+
+/// Read the position from a file, or standard input for `-`.
+fn load(path: &str) -> Result<Position, String> {
+    if path == "-" {
+        let mut text = String::new();
+        std::io::stdin()
+            .read_to_string(&mut text)
+            .map_err(|err| err.to_string())?;
+        text.parse()
+    } else {
+        Position::load(path)
+    }
+}
@@ -0,0 +1,358 @@
+use crate::game::{GridState, MinesweeperGame};
+use crate::sprites::{SpriteType, Sprites};
+use egui::{
+    Align, Align2, Color32, FontId, PointerButton, Rect, Sense, Stroke, StrokeKind, Ui, vec2,
+};
+
+/// Largest side of the minimap, in points.
+pub const MINIMAP_SIZE: f32 = 160.0;
+
+const BUTTONS: [PointerButton; 3] = [
+    PointerButton::Primary,
+    PointerButton::Secondary,
+    PointerButton::Middle,
+];
+
+/// Mine probabilities to tint over the unrevealed positions of the grid.
+pub struct Heatmap<'a> {
+    pub probabilities: &'a [Option<f64>],
+    pub labels: bool,
+}
+
+/// A mouse press on the grid that hasn't been released yet.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GridPress {
+    /// Left button on a block, revealing it on release unless the pointer leaves it first.
+    Reveal(u32, u32),
+    /// Right button on a block, flagging it on release unless the pointer leaves it first, or the
+    /// left button goes down too and makes it a chord.
+    Flag(u32, u32),
+    /// Middle button, or left and right together, revealing around a count on release.
+    Chord,
+}
+
+/// What the player did on the grid this frame.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GridAction {
+    Reveal(u32, u32),
+    Flag(u32, u32),
+    Chord(u32, u32),
+}
+
+impl GridAction {
+    pub fn position(self) -> (u32, u32) {
+        match self {
+            GridAction::Reveal(x, y) | GridAction::Flag(x, y) | GridAction::Chord(x, y) => (x, y),
+        }
+    }
+}
+
+/// A mouse button going down or up over the board.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum BoardEvent {
+    /// Pressed over a block.
+    Pressed(PointerButton, (u32, u32)),
+    /// Released, over a block or outside the board.
+    Released(PointerButton, Option<(u32, u32)>),
+}
+
+/// The minesweeper grid as a single widget, meant to go inside a scroll area.
+///
+/// It allocates one rect for the whole board and paints the blocks inside `viewport`, the
+/// visible part of the scroll area, in a single pass, with an optional heatmap over the
+/// unrevealed blocks and a colored outline around each marked block. The pointer is mapped to
+/// blocks directly, rather than through a widget per block.
+///
+/// Like the classic game, the blocks under a held button look pressed and the move happens when
+/// the button is released, so moving the pointer off the pressed block before letting go cancels
+/// it. While chording, the pressed neighbors follow the pointer instead.
+/// Flags are placed when the right button is released, so pressing the left button before then
+/// chords instead. `press` keeps track of the held buttons from one frame to the next. Dragging with Ctrl held pans the board instead.
+pub struct BoardWidget<'a> {
+    pub sprites: &'a Sprites,
+    pub game: &'a MinesweeperGame,
+    pub press: &'a mut Option<GridPress>,
+    /// Size of a sprite pixel, in points.
+    pub zoom: f32,
+    /// Show the whole board, as if every block were revealed.
+    pub show_all: bool,
+    pub heatmap: Option<Heatmap<'a>>,
+    pub marks: &'a [(u32, u32, Color32)],
+    /// Part of the grid in view, from its top left corner.
+    pub viewport: Rect,
+    /// Block to scroll into view, and where to put it.
+    pub scroll_to: Option<((u32, u32), Option<Align>)>,
+}
+
+impl BoardWidget<'_> {
+    /// Draw the board, returning the move the player made this frame, if any.
+    pub fn show(self, ui: &mut Ui) -> Option<GridAction> {
+        let game = self.game;
+        let block = 16.0 * self.zoom;
+        let (grid_rect, response) = ui.allocate_exact_size(
+            vec2(game.width() as f32, game.height() as f32) * block,
+            Sense::click_and_drag(),
+        );
+        let block_rect = |x: u32, y: u32| {
+            Rect::from_min_size(
+                grid_rect.min + vec2(x as f32, y as f32) * block,
+                vec2(block, block),
+            )
+        };
+
+        if let Some(((x, y), align)) = self.scroll_to {
+            ui.scroll_to_rect(block_rect(x, y), align);
+        }
+
+        // pan with Ctrl and the left button, rather than pressing blocks
+        let panning = ui.input(|i| i.modifiers.command);
+        if panning {
+            if response.dragged_by(PointerButton::Primary) {
+                ui.scroll_with_delta(response.drag_delta());
+            }
+            *self.press = None;
+        }
+
+        // the block under the pointer, unless something else is in front of the grid, or the
+        // pointer has been dragged off it
+        let hovered = response
+            .hover_pos()
+            .filter(|&pos| grid_rect.contains(pos) && ui.clip_rect().contains(pos))
+            .map(|pos| {
+                let offset = (pos - grid_rect.min) / block;
+                (
+                    (offset.x as u32).min(game.width() - 1),
+                    (offset.y as u32).min(game.height() - 1),
+                )
+            });
+
+        let mut events = Vec::new();
+        let (primary_down, any_down) = ui.input(|i| {
+            for button in BUTTONS {
+                if i.pointer.button_pressed(button)
+                    && let Some(cell) = hovered
+                    && !panning
+                {
+                    events.push(BoardEvent::Pressed(button, cell));
+                }
+                if i.pointer.button_released(button) {
+                    events.push(BoardEvent::Released(button, hovered));
+                }
+            }
+            (i.pointer.primary_down(), i.pointer.any_down())
+        });
+
+        let action = if game.game_over() {
+            *self.press = None;
+            None
+        } else {
+            track_press(self.press, &events, hovered, primary_down, any_down)
+        };
+
+        // blocks that look pressed: the one under the pointer, and its neighbors while chording
+        let mut pressed_blocks = Vec::new();
+        match (*self.press, hovered) {
+            (Some(GridPress::Reveal(x, y)), _) => pressed_blocks.push((x, y)),
+            (Some(GridPress::Chord), Some((x, y))) => pressed_blocks.extend(game.neighbors(x, y)),
+            _ => {}
+        }
+
+        // only the blocks in view
+        let first = (self.viewport.min.to_vec2() / block)
+            .floor()
+            .max(vec2(0.0, 0.0));
+        let last = (self.viewport.max.to_vec2() / block).ceil();
+        let (x0, y0) = (first.x as u32, first.y as u32);
+        let x1 = (last.x.max(0.0) as u32).min(game.width());
+        let y1 = (last.y.max(0.0) as u32).min(game.height());
+
+        let mut blocks = Vec::with_capacity(((x1 - x0.min(x1)) * (y1 - y0.min(y1))) as usize);
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let state = game.peek_at(x, y, self.show_all);
+                let sprite = if state == GridState::Unrevealed && pressed_blocks.contains(&(x, y)) {
+                    SpriteType::BlockEmptyDown
+                } else {
+                    sprite_for_grid(state)
+                };
+                blocks.push((sprite, block_rect(x, y)));
+            }
+        }
+        self.sprites.paint_all(ui, &blocks);
+
+        let painter = ui.painter();
+        if let Some(heatmap) = &self.heatmap {
+            for y in y0..y1 {
+                for x in x0..x1 {
+                    let state = game.peek_at(x, y, self.show_all);
+                    let Some(p) = heatmap.probabilities[(x + y * game.width()) as usize] else {
+                        continue;
+                    };
+                    if !matches!(state, GridState::Unrevealed | GridState::Flagged) {
+                        continue;
+                    }
+                    let rect = block_rect(x, y);
+                    painter.rect_filled(rect, 0.0, heatmap_color(p));
+                    if heatmap.labels {
+                        painter.text(
+                            rect.center(),
+                            Align2::CENTER_CENTER,
+                            format!("{:.0}", p * 100.0),
+                            FontId::proportional(5.5 * self.zoom),
+                            Color32::BLACK,
+                        );
+                    }
+                }
+            }
+        }
+
+        for &(x, y, color) in self.marks {
+            if (x0..x1).contains(&x) && (y0..y1).contains(&y) {
+                painter.rect_stroke(
+                    block_rect(x, y),
+                    0.0,
+                    Stroke::new(2.0, color),
+                    StrokeKind::Inside,
+                );
+            }
+        }
+
+        action
+    }
+}
+
+/// Follow the held buttons through this frame's events, returning the move made when they're
+/// released.
+fn track_press(
+    press: &mut Option<GridPress>,
+    events: &[BoardEvent],
+    hovered: Option<(u32, u32)>,
+    primary_down: bool,
+    any_down: bool,
+) -> Option<GridAction> {
+    let mut action = None;
+    for &event in events {
+        match (*press, event) {
+            // releasing either button of a chord finishes it, and the other button does nothing
+            (Some(current), BoardEvent::Released(_, cell)) => {
+                action = match (current, cell) {
+                    (GridPress::Reveal(x, y), Some(cell)) if cell == (x, y) => {
+                        Some(GridAction::Reveal(x, y))
+                    }
+                    (GridPress::Flag(x, y), Some(cell)) if cell == (x, y) => {
+                        Some(GridAction::Flag(x, y))
+                    }
+                    (GridPress::Chord, Some((x, y))) => Some(GridAction::Chord(x, y)),
+                    _ => None,
+                };
+                *press = None;
+            }
+            (_, BoardEvent::Pressed(PointerButton::Middle, _)) => *press = Some(GridPress::Chord),
+            // the second button of a chord, pressed in either order
+            (
+                Some(_),
+                BoardEvent::Pressed(PointerButton::Primary | PointerButton::Secondary, _),
+            ) => {
+                *press = Some(GridPress::Chord);
+            }
+            (None, BoardEvent::Pressed(PointerButton::Secondary, _)) if primary_down => {
+                *press = Some(GridPress::Chord);
+            }
+            (None, BoardEvent::Pressed(PointerButton::Secondary, (x, y))) => {
+                *press = Some(GridPress::Flag(x, y));
+            }
+            (None, BoardEvent::Pressed(PointerButton::Primary, (x, y))) => {
+                *press = Some(GridPress::Reveal(x, y));
+            }
+            _ => {}
+        }
+    }
+
+    // dragging off the pressed block cancels the reveal or flag
+    if let Some(GridPress::Reveal(x, y) | GridPress::Flag(x, y)) = *press
+        && hovered != Some((x, y))
+    {
+        *press = None;
+    }
+
+    // a release that never arrived, such as outside the window, cancels the press
+    if !any_down {
+        *press = None;
+    }
+    action
+}
+
+/// A small picture of the whole board, tinted by what's revealed and flagged, with the part in
+/// view outlined. Pressing or dragging on it returns the block to bring into view.
+pub fn minimap(
+    ui: &mut Ui,
+    texture: &egui::TextureHandle,
+    game: &MinesweeperGame,
+    visible: Rect,
+) -> Option<(u32, u32)> {
+    let board = vec2(game.width() as f32, game.height() as f32);
+    let scale = (MINIMAP_SIZE / board.x).min(MINIMAP_SIZE / board.y);
+    let response = ui.add(
+        egui::Image::new(texture)
+            .fit_to_exact_size(board * scale)
+            .sense(Sense::click_and_drag()),
+    );
+    let rect = response.rect;
+
+    let outline = Rect::from_min_max(
+        rect.min + visible.min.to_vec2() * scale,
+        rect.min + visible.max.to_vec2() * scale,
+    )
+    .intersect(rect);
+    ui.painter().rect_stroke(
+        outline,
+        0.0,
+        Stroke::new(1.5, Color32::from_rgb(0, 90, 255)),
+        StrokeKind::Outside,
+    );
+
+    let pos = response.interact_pointer_pos()?;
+    let cell = ((pos - rect.min) / scale).max(vec2(0.0, 0.0));
+    Some((
+        (cell.x as u32).min(game.width() - 1),
+        (cell.y as u32).min(game.height() - 1),
+    ))
+}
+
+/// One pixel per block, for the minimap.
+pub fn minimap_image(game: &MinesweeperGame) -> egui::ColorImage {
+    let mut pixels = Vec::with_capacity(game.total_size());
+    for y in 0..game.height() {
+        for x in 0..game.width() {
+            pixels.push(match game.peek_at(x, y, false) {
+                GridState::Unrevealed => Color32::from_rgb(128, 128, 128),
+                GridState::Flagged => Color32::from_rgb(220, 0, 0),
+                GridState::Mine | GridState::MineHighlighted | GridState::MineIncorrect => {
+                    Color32::BLACK
+                }
+                GridState::Empty | GridState::Count(_) => Color32::from_rgb(220, 220, 220),
+            });
+        }
+    }
+    egui::ColorImage::new([game.width() as usize, game.height() as usize], pixels)
+}
+
+/// Translucent green for safe positions, through yellow, to red for certain mines.
+fn heatmap_color(p: f64) -> Color32 {
+    let p = p.clamp(0.0, 1.0) as f32;
+    let r = (2.0 * p).min(1.0);
+    let g = (2.0 * (1.0 - p)).min(1.0);
+    Color32::from_rgba_unmultiplied((r * 255.0) as u8, (g * 255.0) as u8, 0, 120)
+}
+
+fn sprite_for_grid(state: GridState) -> SpriteType {
+    match state {
+        GridState::Empty => SpriteType::BlockEmptyDown,
+        GridState::Count(count) => SpriteType::block_digit(count.into()),
+        GridState::Mine => SpriteType::BlockMine,
+        GridState::Unrevealed => SpriteType::BlockEmptyUp,
+        GridState::Flagged => SpriteType::BlockFlag,
+        GridState::MineHighlighted => SpriteType::BlockMineRed,
+        GridState::MineIncorrect => SpriteType::BlockMineX,
+    }
+}
@@ -1,6 +1,14 @@
+use rand::Rng;
 use rand::prelude::SliceRandom;
+use std::path::Path;
 use std::time::{Duration, Instant};
 
+/// Time added to the clock for each hint the player asks for.
+pub const HINT_PENALTY: Duration = Duration::from_secs(10);
+
+/// File a game in progress is saved to, in the game's data folder.
+pub const SAVE_FILE: &str = "game.txt";
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct GameConfig {
     width: u32,
@@ -88,6 +96,7 @@ pub enum GridState {
     MineIncorrect,
 }
 
+#[derive(Clone)]
 pub struct MinesweeperGame {
     config: GameConfig,
     state: GameState,
@@ -96,6 +105,10 @@ pub struct MinesweeperGame {
     revealed: Vec<bool>,
     flagged_count: usize,
     revealed_count: usize,
+    moves: Vec<GameMove>,
+    /// Positions revealed before the game started, such as the given counts of a puzzle.
+    givens: Vec<usize>,
+    hints_used: usize,
     timer: Timer,
 }
 
@@ -107,7 +120,12 @@ impl Default for MinesweeperGame {
 
 impl MinesweeperGame {
     pub fn new(config: GameConfig) -> Self {
-        let mines = generate_mines(config.mines(), config.total_size());
+        MinesweeperGame::with_rng(config, &mut rand::rng())
+    }
+
+    /// Create a game with mines placed using the given random number generator, for repeatable layouts.
+    pub fn with_rng(config: GameConfig, rng: &mut impl Rng) -> Self {
+        let mines = generate_mines(config.mines(), config.total_size(), rng);
         MinesweeperGame::with_mines(config, &mines)
     }
 
@@ -134,10 +152,31 @@ impl MinesweeperGame {
             revealed,
             revealed_count: 0,
             flagged_count: 0,
+            moves: Vec::new(),
+            givens: Vec::new(),
+            hints_used: 0,
             timer: Timer::default(),
         }
     }
 
+    /// Create a game with some safe positions already revealed, such as the given counts of a
+    /// puzzle. Given positions don't open up their neighbors, and aren't recorded as moves.
+    pub fn with_givens(config: GameConfig, mine_positions: &[usize], givens: &[usize]) -> Self {
+        let mut game = MinesweeperGame::with_mines(config, mine_positions);
+        for &i in givens {
+            assert!(
+                game.grid[i] != GridState::Mine,
+                "Given positions must be safe"
+            );
+            if !game.revealed[i] {
+                game.revealed[i] = true;
+                game.revealed_count += 1;
+            }
+        }
+        game.givens = givens.to_vec();
+        game
+    }
+
     pub fn config(&self) -> GameConfig {
         self.config
     }
@@ -183,6 +222,45 @@ impl MinesweeperGame {
         self.total_size() - self.revealed_count - self.flagged_count
     }
 
+    pub fn hints_used(&self) -> usize {
+        self.hints_used
+    }
+
+    /// Count a hint given to the player, adding `HINT_PENALTY` to the clock, including hints asked
+    /// for before the first move, such as on a puzzle.
+    pub fn use_hint(&mut self) {
+        if !self.state.game_over() {
+            self.hints_used += 1;
+            self.timer.add_penalty(HINT_PENALTY);
+        }
+    }
+
+    /// Positions revealed before the game started, for replaying with `with_givens`.
+    pub fn givens(&self) -> &[usize] {
+        &self.givens
+    }
+
+    /// Every move that changed the game, in the order they were made.
+    pub fn moves(&self) -> &[GameMove] {
+        &self.moves
+    }
+
+    /// Index of every mine, for replaying the same layout with `with_mines`.
+    pub fn mine_positions(&self) -> Vec<usize> {
+        (0..self.grid.len())
+            .filter(|&i| self.grid[i] == GridState::Mine)
+            .collect()
+    }
+
+    /// Actual contents of every position, as produced by `initialize_grid`.
+    pub fn layout(&self) -> &[GridState] {
+        &self.grid
+    }
+
+    pub fn is_flagged(&self, x: u32, y: u32) -> bool {
+        self.flagged[pos_to_index(x, y, self.width())]
+    }
+
     pub fn neighbors(&self, x: u32, y: u32) -> Vec<(u32, u32)> {
         // Naively, we'd like to look at [x-1, x, x+1], but because x/y are u32, we can't actually calculate x-1 when x is 0.
         // So use a little trick to check bounds+1 on [x, x+1, x+2], and then subtract 1 when in bounds.
@@ -252,6 +330,14 @@ impl MinesweeperGame {
     }
 
     pub fn reveal(&mut self, x: u32, y: u32) -> bool {
+        let revealed = self.reveal_inner(x, y);
+        if revealed {
+            self.moves.push(GameMove::Reveal(x, y));
+        }
+        revealed
+    }
+
+    fn reveal_inner(&mut self, x: u32, y: u32) -> bool {
         if self.game_over() {
             return false;
         }
@@ -272,7 +358,7 @@ impl MinesweeperGame {
             GridState::Empty => {
                 // Also reveal neighbors
                 for (nx, ny) in self.neighbors(x, y) {
-                    self.reveal(nx, ny);
+                    self.reveal_inner(nx, ny);
                 }
             }
             GridState::Count(_) => {}
@@ -292,7 +378,7 @@ impl MinesweeperGame {
                 self.timer.end();
                 // Flag all mines when game ends successfully
                 for j in 0..self.grid.len() {
-                    if self.grid[j] == GridState::Mine {
+                    if self.grid[j] == GridState::Mine && !self.flagged[j] {
                         self.flagged[j] = true;
                         self.flagged_count += 1;
                     }
@@ -303,6 +389,175 @@ impl MinesweeperGame {
         true
     }
 
+    /// Reveal every unflagged neighbor of a revealed count, once it has as many flags around it
+    /// as its count. Returns true if anything was revealed.
+    pub fn chord(&mut self, x: u32, y: u32) -> bool {
+        let i = pos_to_index(x, y, self.width());
+        let GridState::Count(count) = self.grid[i] else {
+            return false;
+        };
+        if !self.revealed[i] || self.game_over() {
+            return false;
+        }
+
+        let neighbors = self.neighbors(x, y);
+        let flags = neighbors
+            .iter()
+            .filter(|&&(nx, ny)| self.is_flagged(nx, ny))
+            .count();
+        if flags != count as usize {
+            return false;
+        }
+
+        let mut revealed = false;
+        for (nx, ny) in neighbors {
+            revealed |= self.reveal(nx, ny);
+        }
+        revealed
+    }
+
+    /// The game as text, to carry on with later using `from_saved`.
+    ///
+    /// The board is written one row per line: `.` for a hidden safe position and `*` for a hidden
+    /// mine, `f` and `F` for the same when flagged, `o` for a revealed safe position, `X` for a
+    /// revealed mine and `g` for a position given from the start. A `time` line gives the
+    /// milliseconds on the clock before any hint penalty, a `hints` line the hints asked for, and
+    /// each move follows in order as `reveal X Y` or `flag X Y`. Lines starting with `#` are
+    /// comments.
+    pub fn to_saved(&self) -> String {
+        let mut text = String::from("# minesweeper game\n");
+        text += &format!("time {}\n", self.timer.clock().as_millis());
+        text += &format!("hints {}\n", self.hints_used);
+        for y in 0..self.height() {
+            let row: String = (0..self.width())
+                .map(|x| {
+                    let i = pos_to_index(x, y, self.width());
+                    let mine = self.grid[i] == GridState::Mine;
+                    match (
+                        self.givens.contains(&i),
+                        self.revealed[i],
+                        self.flagged[i],
+                        mine,
+                    ) {
+                        (true, ..) => 'g',
+                        (_, true, _, true) => 'X',
+                        (_, true, _, false) => 'o',
+                        (_, false, true, true) => 'F',
+                        (_, false, true, false) => 'f',
+                        (_, false, false, true) => '*',
+                        (_, false, false, false) => '.',
+                    }
+                })
+                .collect();
+            text += &row;
+            text.push('\n');
+        }
+        for m in &self.moves {
+            match m {
+                GameMove::Reveal(x, y) => text += &format!("reveal {x} {y}\n"),
+                GameMove::Flag(x, y) => text += &format!("flag {x} {y}\n"),
+                GameMove::NoOp => {}
+            }
+        }
+        text
+    }
+
+    /// Carry on with a game written by `to_saved`, replaying its moves on the same layout and
+    /// checking they lead to the board that was saved.
+    pub fn from_saved(text: &str) -> Result<Self, String> {
+        let mut clock = Duration::ZERO;
+        let mut hints = 0;
+        let mut rows: Vec<Vec<char>> = Vec::new();
+        let mut moves = Vec::new();
+
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |what: &str| format!("line {}: invalid {what}: {line}", n + 1);
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let number = |field: &str, what: &str| field.parse::<u64>().map_err(|_| error(what));
+            match fields[..] {
+                ["time", millis] => clock = Duration::from_millis(number(millis, "time")?),
+                ["hints", count] => hints = number(count, "hints")? as usize,
+                [kind @ ("reveal" | "flag"), x, y] => {
+                    let (x, y) = (number(x, "move")? as u32, number(y, "move")? as u32);
+                    moves.push(if kind == "reveal" {
+                        GameMove::Reveal(x, y)
+                    } else {
+                        GameMove::Flag(x, y)
+                    });
+                }
+                [row] if row.chars().all(|c| ".*fFoXg".contains(c)) => {
+                    let row: Vec<char> = row.chars().collect();
+                    if let Some(first) = rows.first()
+                        && first.len() != row.len()
+                    {
+                        return Err(format!(
+                            "line {}: expected {} positions, found {}",
+                            n + 1,
+                            first.len(),
+                            row.len()
+                        ));
+                    }
+                    rows.push(row);
+                }
+                _ => return Err(format!("line {}: not part of a saved game: {line}", n + 1)),
+            }
+        }
+
+        if rows.is_empty() {
+            return Err("no board given".to_string());
+        }
+        let (width, height) = (rows[0].len() as u32, rows.len() as u32);
+        let cells: Vec<char> = rows.into_iter().flatten().collect();
+        let mines: Vec<usize> = (0..cells.len())
+            .filter(|&i| matches!(cells[i], '*' | 'F' | 'X'))
+            .collect();
+        let givens: Vec<usize> = (0..cells.len()).filter(|&i| cells[i] == 'g').collect();
+
+        let config = GameConfig::new(width, height, mines.len());
+        let mut game = MinesweeperGame::with_givens(config, &mines, &givens);
+        for &m in &moves {
+            if let GameMove::Reveal(x, y) | GameMove::Flag(x, y) = m
+                && (x >= width || y >= height)
+            {
+                return Err(format!("move outside the board: {m:?}"));
+            }
+            game.make_move(m);
+        }
+
+        let matches = game.moves == moves
+            && (0..cells.len()).all(|i| {
+                let revealed = matches!(cells[i], 'o' | 'X' | 'g');
+                let flagged = matches!(cells[i], 'f' | 'F');
+                game.revealed[i] == revealed && game.flagged[i] == flagged
+            });
+        if !matches {
+            return Err("the moves don't lead to the saved board".to_string());
+        }
+
+        game.hints_used = hints;
+        game.timer.penalty = HINT_PENALTY * hints as u32;
+        if game.timer.is_started() {
+            game.timer.resume(clock, game.game_over());
+        }
+        Ok(game)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_saved()).map_err(|err| format!("{}: {err}", path.display()))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let text =
+            std::fs::read_to_string(path).map_err(|err| format!("{}: {err}", path.display()))?;
+        MinesweeperGame::from_saved(&text).map_err(|err| format!("{}: {err}", path.display()))
+    }
+
     pub fn toggle_flag(&mut self, x: u32, y: u32) -> bool {
         if self.game_over() {
             return false;
@@ -321,20 +576,21 @@ impl MinesweeperGame {
                 self.flagged[i] = true;
                 self.flagged_count += 1;
             }
+            self.moves.push(GameMove::Flag(x, y));
         }
         self.flagged[i]
     }
 }
 
-fn pos_to_index(x: u32, y: u32, width: u32) -> usize {
+pub fn pos_to_index(x: u32, y: u32, width: u32) -> usize {
     (x + y * width) as usize
 }
 
-fn index_to_pos(i: usize, width: u32) -> (u32, u32) {
+pub fn index_to_pos(i: usize, width: u32) -> (u32, u32) {
     (i as u32 % width, i as u32 / width)
 }
 
-fn generate_mines(mines: usize, size: usize) -> Vec<usize> {
+fn generate_mines(mines: usize, size: usize, rng: &mut impl Rng) -> Vec<usize> {
     let mut result: Vec<usize> = (0..size).collect();
 
     // shortcut to handle 100% density
@@ -342,13 +598,12 @@ fn generate_mines(mines: usize, size: usize) -> Vec<usize> {
         return result;
     }
 
-    let mut rng = rand::rng();
-    result.shuffle(&mut rng);
+    result.shuffle(rng);
 
     result.into_iter().take(mines).collect()
 }
 
-fn initialize_grid(config: &GameConfig, mine_positions: &[usize]) -> Vec<GridState> {
+pub fn initialize_grid(config: &GameConfig, mine_positions: &[usize]) -> Vec<GridState> {
     let size = config.total_size();
     let mut grid = vec![GridState::Empty; size];
 
@@ -422,18 +677,14 @@ fn initialize_grid(config: &GameConfig, mine_positions: &[usize]) -> Vec<GridSta
     grid
 }
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 struct Timer {
     start_time: Option<Instant>,
     end_time: Option<Instant>,
+    penalty: Duration,
 }
 
 impl Timer {
-    fn reset(&mut self) {
-        self.start_time = None;
-        self.end_time = None;
-    }
-
     fn start(&mut self) {
         self.start_time = Some(Instant::now());
         self.end_time = None;
@@ -448,15 +699,54 @@ impl Timer {
         self.start_time.is_some()
     }
 
-    fn is_ended(&self) -> bool {
-        self.end_time.is_some()
+    /// Carry on from a saved game, as if the clock had been running for `clock` already.
+    fn resume(&mut self, clock: Duration, ended: bool) {
+        let now = Instant::now();
+        self.start_time = Some(now.checked_sub(clock).unwrap_or(now));
+        self.end_time = ended.then_some(now);
     }
 
-    fn elapsed_duration(&self) -> Duration {
+    fn add_penalty(&mut self, penalty: Duration) {
+        self.penalty += penalty;
+    }
+
+    /// Time since the first move, without the penalty.
+    fn clock(&self) -> Duration {
         match (self.start_time, self.end_time) {
             (Some(start_time), None) => start_time.elapsed(),
             (Some(start_time), Some(end_time)) => end_time.duration_since(start_time),
             (None, _) => Duration::ZERO,
         }
     }
+
+    fn elapsed_duration(&self) -> Duration {
+        self.clock() + self.penalty
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saved_game_carries_on() {
+        let mut game = MinesweeperGame::with_mines(GameConfig::new(4, 3, 2), &[0, 11]);
+        game.make_move(GameMove::Reveal(2, 0));
+        game.make_move(GameMove::Flag(0, 0));
+        game.use_hint();
+
+        let saved = game.to_saved();
+        let loaded = MinesweeperGame::from_saved(&saved).unwrap();
+        assert_eq!(loaded.to_saved(), saved);
+        assert_eq!(loaded.moves(), game.moves());
+        assert_eq!(loaded.mine_positions(), vec![0, 11]);
+        assert_eq!(loaded.hints_used(), 1);
+        assert!(loaded.timer_elapsed() >= HINT_PENALTY);
+    }
+
+    #[test]
+    fn saved_moves_must_match_board() {
+        let text = "time 0\nhints 0\n*...\n...*\nreveal 1 0\n";
+        assert!(MinesweeperGame::from_saved(text).is_err());
+    }
 }
@@ -0,0 +1,7 @@
+#![warn(clippy::all, rust_2018_idioms)]
+
+pub mod analysis;
+pub mod game;
+pub mod puzzle;
+pub mod solver;
+pub mod stats;
@@ -1,11 +1,14 @@
 #![warn(clippy::all, rust_2018_idioms)]
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows in release
-#![allow(dead_code)]
+
+use minesweeper::{analysis, game, puzzle, solver, stats};
 
 mod app;
-mod game;
-mod solver;
+mod autoplay;
+mod board;
 mod sprites;
+mod theme;
+mod vector;
 
 fn main() -> eframe::Result<()> {
     // Log to stdout (if you run with `RUST_LOG=debug`).
@@ -13,11 +16,13 @@ fn main() -> eframe::Result<()> {
 
     let native_options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default().with_resizable(false),
+        // reopen where the window was last closed, along with the saved settings
+        persist_window: true,
         ..Default::default()
     };
 
     eframe::run_native(
-        "Minesweeper",
+        app::APP_NAME,
         native_options,
         Box::new(|cc| {
             egui_extras::install_image_loaders(&cc.egui_ctx);
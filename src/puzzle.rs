@@ -0,0 +1,363 @@
+use crate::game::{GameConfig, GridState, MinesweeperGame, initialize_grid};
+use crate::solver::{Hint, solve_position};
+use rand::Rng;
+use rand::prelude::SliceRandom;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// What is given about a single position of a puzzle.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PositionCell {
+    Unknown,
+    /// Known not to be a mine, without knowing its count.
+    Safe,
+    Mine,
+    Revealed(u8),
+}
+
+impl PositionCell {
+    fn to_char(self) -> char {
+        match self {
+            PositionCell::Unknown => '.',
+            PositionCell::Safe => 'o',
+            PositionCell::Mine => '*',
+            PositionCell::Revealed(count) => char::from(b'0' + count),
+        }
+    }
+
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            '.' | '?' => Some(PositionCell::Unknown),
+            'o' => Some(PositionCell::Safe),
+            '*' | 'F' => Some(PositionCell::Mine),
+            '0'..='8' => Some(PositionCell::Revealed(c as u8 - b'0')),
+            _ => None,
+        }
+    }
+}
+
+/// A partially known board, where the mine layout may not be known at all.
+///
+/// Positions are written one row per line: `.` for unknown, `0`-`8` for a revealed count, `*`
+/// for a mine and `o` for a position known to be safe. An optional `mines N` line gives the
+/// total number of mines, and lines starting with `#` are comments.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Position {
+    width: u32,
+    height: u32,
+    mines: Option<usize>,
+    cells: Vec<PositionCell>,
+}
+
+impl Position {
+    /// A position where nothing is known yet.
+    pub fn new(width: u32, height: u32, mines: Option<usize>) -> Self {
+        Position {
+            width,
+            height,
+            mines,
+            cells: vec![PositionCell::Unknown; (width * height) as usize],
+        }
+    }
+
+    /// The position the player sees, with revealed counts and the total number of mines.
+    ///
+    /// Flags are left out, since the player may have placed them wrongly.
+    pub fn from_game(game: &MinesweeperGame) -> Self {
+        let mut position = Position::new(game.width(), game.height(), Some(game.config().mines()));
+        for y in 0..game.height() {
+            for x in 0..game.width() {
+                match game.peek_at(x, y, false) {
+                    GridState::Empty => position.set(x, y, PositionCell::Revealed(0)),
+                    GridState::Count(count) => position.set(x, y, PositionCell::Revealed(count)),
+                    _ => {}
+                }
+            }
+        }
+        position
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        std::fs::read_to_string(path)
+            .map_err(|err| format!("{}: {err}", path.display()))?
+            .parse()
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_string()).map_err(|err| format!("{}: {err}", path.display()))
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Total number of mines, if known.
+    pub fn mines(&self) -> Option<usize> {
+        self.mines
+    }
+
+    pub fn cell(&self, x: u32, y: u32) -> PositionCell {
+        self.cells[(x + y * self.width) as usize]
+    }
+
+    pub fn set(&mut self, x: u32, y: u32, cell: PositionCell) {
+        self.cells[(x + y * self.width) as usize] = cell;
+    }
+
+    /// Generate a logic puzzle: a random mine layout, with a minimal set of given counts from which
+    /// the rest of the board can be deduced.
+    ///
+    /// Starts with every count given, then removes them one at a time in random order, putting
+    /// back any whose removal leaves more than one solution. The total number of mines is given.
+    pub fn generate(config: GameConfig, rng: &mut impl Rng) -> Self {
+        let mines = MinesweeperGame::with_rng(config, rng).mine_positions();
+        let grid = initialize_grid(&config, &mines);
+
+        let mut position = Position::new(config.width(), config.height(), Some(config.mines()));
+        let mut clues = Vec::new();
+        for (i, state) in grid.iter().enumerate() {
+            let cell = match *state {
+                GridState::Empty => PositionCell::Revealed(0),
+                GridState::Count(count) => PositionCell::Revealed(count),
+                _ => continue,
+            };
+            position.cells[i] = cell;
+            clues.push(i);
+        }
+
+        clues.shuffle(rng);
+        for i in clues {
+            let clue = position.cells[i];
+            position.cells[i] = PositionCell::Unknown;
+            if solve_position(&position).uniqueness != Uniqueness::Unique {
+                position.cells[i] = clue;
+            }
+        }
+
+        position
+    }
+
+    /// A game to play this puzzle, with its given counts and safe positions already revealed.
+    ///
+    /// Only puzzles with a unique solution can be played, since the game needs the mine layout.
+    pub fn to_game(&self) -> Result<MinesweeperGame, String> {
+        let solution = solve_position(self);
+        if solution.uniqueness != Uniqueness::Unique {
+            return Err(format!(
+                "can't play a puzzle without a unique solution: {}",
+                solution.uniqueness.description().to_lowercase()
+            ));
+        }
+
+        let mut solved = self.clone();
+        for step in &solution.steps {
+            solved.apply(step);
+        }
+
+        let mut mines = Vec::new();
+        let mut givens = Vec::new();
+        for (i, (&given, &cell)) in self.cells.iter().zip(&solved.cells).enumerate() {
+            if cell == PositionCell::Mine {
+                mines.push(i);
+            } else if given != PositionCell::Unknown {
+                givens.push(i);
+            }
+        }
+
+        let config = GameConfig::new(self.width, self.height, mines.len());
+        Ok(MinesweeperGame::with_givens(config, &mines, &givens))
+    }
+
+    /// Record what a deduction proved about its position.
+    pub fn apply(&mut self, hint: &Hint) {
+        let cell = if hint.mine {
+            PositionCell::Mine
+        } else {
+            PositionCell::Safe
+        };
+        self.set(hint.x, hint.y, cell);
+    }
+}
+
+impl FromStr for Position {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut mines = None;
+        let mut rows: Vec<Vec<PositionCell>> = Vec::new();
+
+        for (n, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(value) = line.strip_prefix("mines") {
+                let value = value.trim();
+                mines =
+                    Some(value.parse().map_err(|_| {
+                        format!("line {}: invalid number of mines: {value}", n + 1)
+                    })?);
+                continue;
+            }
+
+            let row = line
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .map(|c| {
+                    PositionCell::from_char(c)
+                        .ok_or_else(|| format!("line {}: invalid position: {c}", n + 1))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if let Some(first) = rows.first()
+                && first.len() != row.len()
+            {
+                return Err(format!(
+                    "line {}: expected {} positions, found {}",
+                    n + 1,
+                    first.len(),
+                    row.len()
+                ));
+            }
+            rows.push(row);
+        }
+
+        if rows.is_empty() {
+            return Err("no positions given".to_string());
+        }
+
+        let width = rows[0].len() as u32;
+        let height = rows.len() as u32;
+        let cells: Vec<PositionCell> = rows.into_iter().flatten().collect();
+
+        if let Some(mines) = mines {
+            let given = cells.iter().filter(|&&c| c == PositionCell::Mine).count();
+            if mines < given || mines > cells.len() {
+                return Err(format!("{mines} mines can't fit the position"));
+            }
+        }
+
+        Ok(Position {
+            width,
+            height,
+            mines,
+            cells,
+        })
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(mines) = self.mines {
+            writeln!(f, "mines {mines}")?;
+        }
+        for row in self.cells.chunks(self.width as usize) {
+            let line: String = row.iter().map(|c| c.to_char()).collect();
+            writeln!(f, "{line}")?;
+        }
+        Ok(())
+    }
+}
+
+/// How many mine layouts fit a position.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Uniqueness {
+    Unique,
+    Multiple,
+    /// The given counts contradict each other.
+    NoSolution,
+    /// The frontier was too large to enumerate every arrangement.
+    Undecided,
+}
+
+impl Uniqueness {
+    pub fn description(self) -> &'static str {
+        match self {
+            Uniqueness::Unique => "Unique solution",
+            Uniqueness::Multiple => "Multiple solutions",
+            Uniqueness::NoSolution => "No solution",
+            Uniqueness::Undecided => "Too large to decide",
+        }
+    }
+}
+
+/// Everything that can be deduced from a position, in order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PositionSolution {
+    /// Each deduction, in an order where it only depends on the given positions and the
+    /// deductions before it.
+    pub steps: Vec<Hint>,
+    pub uniqueness: Uniqueness,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn readme_puzzle_is_unique() {
+        let position: Position = "mines 3\n.....\n12321\n".parse().unwrap();
+        let solution = solve_position(&position);
+        assert_eq!(solution.uniqueness, Uniqueness::Unique);
+
+        let game = position.to_game().unwrap();
+        assert_eq!(game.mine_positions(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn shared_count_has_multiple_solutions() {
+        let position: Position = "..\n11".parse().unwrap();
+        assert_eq!(solve_position(&position).uniqueness, Uniqueness::Multiple);
+        assert!(position.to_game().is_err());
+    }
+
+    #[test]
+    fn contradicting_counts_have_no_solution() {
+        let position: Position = "..\n02".parse().unwrap();
+        assert_eq!(solve_position(&position).uniqueness, Uniqueness::NoSolution);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            "..\n1x".parse::<Position>(),
+            Err("line 2: invalid position: x".to_string())
+        );
+        assert_eq!(
+            "...\n11".parse::<Position>(),
+            Err("line 2: expected 3 positions, found 2".to_string())
+        );
+        assert_eq!(
+            "mines 5\n..\n11".parse::<Position>(),
+            Err("5 mines can't fit the position".to_string())
+        );
+        assert_eq!(
+            "# only a comment".parse::<Position>(),
+            Err("no positions given".to_string())
+        );
+    }
+
+    #[test]
+    fn display_parses_back() {
+        let text = "mines 2\n*o.\n123\n";
+        let position: Position = text.parse().unwrap();
+        assert_eq!(position.to_string(), text);
+        assert_eq!(position.to_string().parse(), Ok(position));
+    }
+
+    #[test]
+    fn generated_puzzle_is_unique() {
+        let config = GameConfig::new(6, 6, 6);
+        let position = Position::generate(config, &mut StdRng::seed_from_u64(1));
+        assert_eq!(solve_position(&position).uniqueness, Uniqueness::Unique);
+    }
+}
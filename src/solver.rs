@@ -1,87 +1,237 @@
-use crate::game::{GameMove, GridState, MinesweeperGame};
+use crate::game::{GameMove, MinesweeperGame};
 
-pub fn get_next_move(game: &MinesweeperGame) -> GameMove {
-    // no moves possible
-    if game.game_over() {
-        return GameMove::NoOp;
+mod board;
+mod constraint;
+mod linear;
+mod opening;
+mod random;
+mod simple;
+
+pub use constraint::{
+    ConstraintSolver, contradicted_flags, forced_guess, hint, judge_reveal, solve_position,
+};
+pub use opening::{
+    OpeningPolicy, OpeningStats, candidate_openings, learn_opening, learned_opening, study_openings,
+};
+pub use random::RandomSolver;
+pub use simple::SimpleSolver;
+
+/// A strategy for playing minesweeper.
+///
+/// Solvers may keep their own knowledge between moves, so the same solver should be used for the
+/// whole of a game, and `reset` called before starting another.
+pub trait Solver {
+    /// Forget anything learned from a previous game.
+    fn reset(&mut self) {}
+
+    /// Choose where to make the first click. Solvers that don't plan their opening ignore it.
+    fn set_opening(&mut self, _opening: OpeningPolicy) {}
+
+    /// Seed any random choices, so the same games can be played again. Solvers that don't guess at
+    /// random ignore it.
+    fn set_seed(&mut self, _seed: u64) {}
+
+    /// Update internal knowledge from the current position of the game.
+    fn observe(&mut self, _game: &MinesweeperGame) {}
+
+    /// Propose the next move to make in the current position of the game.
+    fn propose_move(&mut self, game: &MinesweeperGame) -> SolverMove;
+
+    /// Observe the game and propose the next move.
+    fn next_move(&mut self, game: &MinesweeperGame) -> SolverMove {
+        self.observe(game);
+        self.propose_move(game)
     }
+}
 
-    let w = game.width();
-    let h = game.height();
+/// A move proposed by a solver, along with how sure it is about it.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SolverMove {
+    pub game_move: GameMove,
+    pub certainty: Certainty,
+}
 
-    // special case for first move: just pick the middle
-    if game.revealed_count() == 0 {
-        return GameMove::Reveal(w / 2, h / 2);
+impl SolverMove {
+    pub const NO_OP: SolverMove = SolverMove::certain(GameMove::NoOp);
+
+    pub const fn certain(game_move: GameMove) -> Self {
+        SolverMove {
+            game_move,
+            certainty: Certainty::Certain,
+        }
     }
 
-    // when there are only as many unrevealed squares as mines remaining, we can just reveal the rest
-    if game.unrevealed_count() as isize == game.mines_remaining() {
-        for y in 0..h {
-            for x in 0..w {
-                let state = game.peek_at(x, y, false);
-                if let GridState::Unrevealed = state {
-                    return GameMove::Reveal(x, y);
-                }
-            }
+    pub const fn guess(game_move: GameMove) -> Self {
+        SolverMove {
+            game_move,
+            certainty: Certainty::Guess,
         }
     }
 
-    // check for any logically consistent moves around a square with a count
-    for y in 0..h {
-        for x in 0..w {
-            let state = game.peek_at(x, y, false);
-            if let GridState::Count(count) = state {
-                if let Some(m) = logical_move_around_count(x, y, count, game) {
-                    return m;
-                }
-            }
+    pub const fn forced(game_move: GameMove, forced: ForcedGuess) -> Self {
+        SolverMove {
+            game_move,
+            certainty: Certainty::Forced(forced),
         }
     }
+}
 
-    // TODO: now we need to guess...
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Certainty {
+    /// The move follows logically from the revealed counts.
+    Certain,
+    /// A guess, though the solver may have missed a safe move.
+    Guess,
+    /// A guess that can't be avoided, since no position is provably safe.
+    Forced(ForcedGuess),
+}
 
-    GameMove::NoOp
+/// A position where every remaining move is a guess.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ForcedGuess {
+    pub kind: ForcedGuessKind,
+    /// Chance that the position picked by the solver is a mine.
+    pub mine_probability: f64,
 }
 
-fn logical_move_around_count(
-    x: u32,
-    y: u32,
-    count: u8,
-    game: &MinesweeperGame,
-) -> Option<GameMove> {
-    // count the unrevealed positions around a point, as well as any existing flags
-    let mut flag_count = 0;
-    let mut unrevealed_count = 0;
-    let mut unrevealed_pos: Option<(u32, u32)> = None;
-
-    for (nx, ny) in game.neighbors(x, y) {
-        let neighbor_state = game.peek_at(nx, ny, false);
-        match neighbor_state {
-            GridState::Empty | GridState::Count(_) => {}
-            GridState::Flagged => {
-                flag_count += 1;
-            }
-            GridState::Unrevealed => {
-                unrevealed_count += 1;
-                unrevealed_pos.get_or_insert((nx, ny));
-            }
-            _ => {
-                return None;
-            }
+impl ForcedGuess {
+    /// The first move is always a guess, with the same chance of a mine everywhere.
+    pub fn opening(game: &MinesweeperGame) -> Self {
+        ForcedGuess {
+            kind: ForcedGuessKind::Opening,
+            mine_probability: game.config().mines() as f64 / game.total_size() as f64,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ForcedGuessKind {
+    /// Nothing has been revealed yet.
+    Opening,
+    /// Positions that can only ever be one of two arrangements, such as a pair with a single
+    /// mine, with no other unknown positions nearby that could tell them apart.
+    FiftyFifty,
+    /// Unknown positions that don't touch any revealed count, such as a region walled off by mines.
+    Isolated,
+    /// Every position along the frontier could still be a mine.
+    Frontier,
+}
+
+impl ForcedGuessKind {
+    pub fn description(self) -> &'static str {
+        match self {
+            ForcedGuessKind::Opening => "opening guess",
+            ForcedGuessKind::FiftyFifty => "50/50 guess",
+            ForcedGuessKind::Isolated => "isolated region",
+            ForcedGuessKind::Frontier => "no safe move",
         }
     }
+}
+
+/// The built-in solvers.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SolverKind {
+    #[default]
+    Simple,
+    Constraint,
+    Random,
+}
 
-    if let Some((nx, ny)) = unrevealed_pos {
-        // if the count on this point matches the number of unresolved neighbors plus any existing flags, then we can flag that unresolved neighbor
-        if count == unrevealed_count + flag_count {
-            return Some(GameMove::Flag(nx, ny));
+impl SolverKind {
+    pub const ALL: [SolverKind; 3] = [
+        SolverKind::Simple,
+        SolverKind::Constraint,
+        SolverKind::Random,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SolverKind::Simple => "Simple",
+            SolverKind::Constraint => "Constraint",
+            SolverKind::Random => "Random",
         }
+    }
 
-        // if the count on this point already matches the number of flags around it, then we can reveal the remaining unresolved neighbors
-        if count == flag_count {
-            return Some(GameMove::Reveal(nx, ny));
+    pub fn create(self) -> Box<dyn Solver> {
+        match self {
+            SolverKind::Simple => Box::<SimpleSolver>::default(),
+            SolverKind::Constraint => Box::<ConstraintSolver>::default(),
+            SolverKind::Random => Box::<RandomSolver>::default(),
         }
     }
+}
+
+/// A position the player can be sure about, and why.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Hint {
+    pub x: u32,
+    pub y: u32,
+    pub mine: bool,
+    pub reason: HintReason,
+}
+
+/// Why a hint is certain, referring to the positions of revealed counts.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum HintReason {
+    /// The count already touches all of its mines, so its other neighbors are safe.
+    CountSatisfied((u32, u32)),
+    /// The count has only as many unknown neighbors as mines it still needs.
+    CountFull((u32, u32)),
+    /// The unknown neighbors of the first count are all shared with the second count.
+    Subset((u32, u32), (u32, u32)),
+    /// Adding and subtracting the counts leaves only one possibility.
+    Equations,
+    /// Every arrangement of mines that fits all the counts agrees.
+    Arrangements,
+}
+
+impl Hint {
+    pub fn description(&self) -> String {
+        // positions are shown to the player counting from 1
+        let at = |(x, y): (u32, u32)| format!("({}, {})", x + 1, y + 1);
+        let what = if self.mine { "Mine" } else { "Safe" };
+        let why = match self.reason {
+            HintReason::CountSatisfied(count) => {
+                format!("the count at {} already touches all its mines", at(count))
+            }
+            HintReason::CountFull(count) => {
+                format!("the count at {} needs all its unknown neighbors", at(count))
+            }
+            HintReason::Subset(smaller, larger) if self.mine => format!(
+                "the count at {} needs more mines than it shares with {}",
+                at(larger),
+                at(smaller)
+            ),
+            HintReason::Subset(smaller, larger) => format!(
+                "the count at {} accounts for every mine near {}",
+                at(smaller),
+                at(larger)
+            ),
+            HintReason::Equations => {
+                "adding and subtracting the counts leaves no other choice".to_string()
+            }
+            HintReason::Arrangements => "every arrangement of mines agrees".to_string(),
+        };
+        format!("{what} at {}: {why}", at((self.x, self.y)))
+    }
+}
+
+/// How a reveal compares with what could be deduced in the position it was made.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RevealJudgement {
+    /// The position was provably safe.
+    Safe,
+    /// A guess, made while some other position was provably safe.
+    Blunder,
+    /// A guess, where no position was provably safe.
+    Forced(ForcedGuessKind),
+    /// A guess, in a position too large to be sure whether anything was safe.
+    Guess,
+}
 
-    None
+/// Exact chance that each position is a mine, judging only from the revealed counts and the total
+/// number of mines, or None for revealed positions. Indexed the same as the game grid.
+pub fn mine_probabilities(game: &MinesweeperGame) -> Vec<Option<f64>> {
+    let board = board::Board::from_game(game);
+    constraint::analyze(&board).probabilities
 }
@@ -0,0 +1,113 @@
+use crate::game::{GridState, MinesweeperGame};
+use crate::puzzle::{Position, PositionCell};
+
+/// What a solver knows about a single position on the board.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Cell {
+    Unknown,
+    Mine,
+    /// Known not to be a mine, though its count isn't known.
+    Safe,
+    Revealed(u8),
+}
+
+/// The board as seen by a solver, with positions stored by index like the game grid.
+pub struct Board {
+    width: u32,
+    height: u32,
+    mines: Option<usize>,
+    cells: Vec<Cell>,
+}
+
+impl Board {
+    /// Build a board from the revealed counts, treating the player's flags as unknown.
+    pub fn from_game(game: &MinesweeperGame) -> Self {
+        let mut cells = Vec::with_capacity(game.total_size());
+        for y in 0..game.height() {
+            for x in 0..game.width() {
+                let cell = match game.peek_at(x, y, false) {
+                    GridState::Empty => Cell::Revealed(0),
+                    GridState::Count(count) => Cell::Revealed(count),
+                    _ => Cell::Unknown,
+                };
+                cells.push(cell);
+            }
+        }
+
+        Board {
+            width: game.width(),
+            height: game.height(),
+            mines: Some(game.config().mines()),
+            cells,
+        }
+    }
+
+    /// Build a board from a puzzle, which may not say how many mines there are.
+    pub fn from_position(position: &Position) -> Self {
+        let mut cells = Vec::new();
+        for y in 0..position.height() {
+            for x in 0..position.width() {
+                let cell = match position.cell(x, y) {
+                    PositionCell::Unknown => Cell::Unknown,
+                    PositionCell::Safe => Cell::Safe,
+                    PositionCell::Mine => Cell::Mine,
+                    PositionCell::Revealed(count) => Cell::Revealed(count),
+                };
+                cells.push(cell);
+            }
+        }
+
+        Board {
+            width: position.width(),
+            height: position.height(),
+            mines: position.mines(),
+            cells,
+        }
+    }
+
+    /// Total number of mines, if known.
+    pub fn mines(&self) -> Option<usize> {
+        self.mines
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn cell(&self, i: usize) -> Cell {
+        self.cells[i]
+    }
+
+    /// Mark a position the solver has already proven to be a mine.
+    pub fn mark_mine(&mut self, i: usize) {
+        self.cells[i] = Cell::Mine;
+    }
+
+    /// Mark a position the solver has proven to be safe, without revealing its count.
+    pub fn mark_safe(&mut self, i: usize) {
+        self.cells[i] = Cell::Safe;
+    }
+
+    pub fn pos(&self, i: usize) -> (u32, u32) {
+        (i as u32 % self.width, i as u32 / self.width)
+    }
+
+    pub fn index(&self, x: u32, y: u32) -> usize {
+        (x + y * self.width) as usize
+    }
+
+    pub fn neighbors(&self, i: usize) -> impl Iterator<Item = usize> + '_ {
+        let (x, y) = self.pos(i);
+        let (x, y) = (x as i64, y as i64);
+        (y - 1..=y + 1)
+            .flat_map(move |ny| (x - 1..=x + 1).map(move |nx| (nx, ny)))
+            .filter(move |&(nx, ny)| {
+                (nx, ny) != (x, y)
+                    && nx >= 0
+                    && ny >= 0
+                    && nx < self.width as i64
+                    && ny < self.height as i64
+            })
+            .map(move |(nx, ny)| self.index(nx as u32, ny as u32))
+    }
+}
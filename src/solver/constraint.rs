@@ -0,0 +1,1034 @@
+use super::board::{Board, Cell};
+use super::linear::linear_deductions;
+use super::{
+    ForcedGuess, ForcedGuessKind, Hint, HintReason, OpeningPolicy, RevealJudgement, Solver,
+    SolverMove,
+};
+use crate::game::{GameMove, MinesweeperGame};
+use crate::puzzle::{Position, PositionSolution, Uniqueness};
+use std::collections::{BTreeSet, HashMap};
+
+/// Maximum number of search steps spent enumerating the arrangements of one frontier component.
+const ENUMERATION_BUDGET: usize = 1_000_000;
+
+/// Solver that combines the constraints from every revealed count.
+///
+/// Tries cheap deductions first, then enumerates every arrangement of mines along the frontier,
+/// and finally guesses the position least likely to be a mine.
+///
+/// The player's flags are never trusted. Instead the solver remembers which positions it has
+/// proven to be mines, and removes any flag from a position it proves to be safe.
+#[derive(Default)]
+pub struct ConstraintSolver {
+    /// Positions proven to be mines, by index.
+    mines: Vec<bool>,
+    opening: OpeningPolicy,
+}
+
+impl Solver for ConstraintSolver {
+    fn reset(&mut self) {
+        self.mines.clear();
+    }
+
+    fn set_opening(&mut self, opening: OpeningPolicy) {
+        self.opening = opening;
+    }
+
+    fn observe(&mut self, game: &MinesweeperGame) {
+        // nothing proven yet for a new game
+        if game.revealed_count() == 0 || self.mines.len() != game.total_size() {
+            self.mines = vec![false; game.total_size()];
+        }
+    }
+
+    fn propose_move(&mut self, game: &MinesweeperGame) -> SolverMove {
+        if game.game_over() {
+            return SolverMove::NO_OP;
+        }
+
+        // special case for first move: nothing to go on, so follow the opening policy
+        if game.revealed_count() == 0 {
+            let (x, y) = self.opening.position(game.config());
+            return SolverMove::forced(GameMove::Reveal(x, y), ForcedGuess::opening(game));
+        }
+
+        let mut board = Board::from_game(game);
+        for (i, &mine) in self.mines.iter().enumerate() {
+            if mine {
+                board.mark_mine(i);
+            }
+        }
+
+        if let Some(constraints) = constraints(&board) {
+            let (safe, mines) = simple_deductions(&board, &constraints);
+            if let Some(m) = self.certain_move(game, &board, &safe, &mines) {
+                return SolverMove::certain(m);
+            }
+
+            // elimination is much cheaper than enumerating a long frontier
+            let (safe, mines) = linear_deductions(&constraints);
+            if let Some(m) = self.certain_move(game, &board, &safe, &mines) {
+                return SolverMove::certain(m);
+            }
+        }
+
+        let analysis = analyze(&board);
+        if let Some(m) = self.certain_move(game, &board, &analysis.safe, &analysis.mines) {
+            return SolverMove::certain(m);
+        }
+
+        // prefer guessing a position without a flag, since revealing a flagged position needs an extra move
+        let guess = analysis
+            .best_guess(|i| {
+                let (x, y) = board.pos(i);
+                !game.is_flagged(x, y)
+            })
+            .or_else(|| analysis.best_guess(|_| true));
+        let Some(i) = guess else {
+            return SolverMove::NO_OP;
+        };
+
+        let (x, y) = board.pos(i);
+        let m = if game.is_flagged(x, y) {
+            GameMove::Flag(x, y)
+        } else {
+            GameMove::Reveal(x, y)
+        };
+
+        match analysis.forced_guess(&board) {
+            Some(kind) => {
+                let mine_probability = analysis.probabilities[i].unwrap_or_default();
+                let forced = ForcedGuess {
+                    kind,
+                    mine_probability,
+                };
+                SolverMove::forced(m, forced)
+            }
+            None => SolverMove::guess(m),
+        }
+    }
+}
+
+impl ConstraintSolver {
+    /// Remember any new mines, then prefer revealing a safe position, then removing a flag from a
+    /// safe position, and finally flagging a known mine.
+    fn certain_move(
+        &mut self,
+        game: &MinesweeperGame,
+        board: &Board,
+        safe: &[usize],
+        mines: &[usize],
+    ) -> Option<GameMove> {
+        for &i in mines {
+            self.mines[i] = true;
+        }
+
+        let flagged = |i: usize| {
+            let (x, y) = board.pos(i);
+            game.is_flagged(x, y)
+        };
+
+        if let Some(&i) = safe.iter().find(|&&i| !flagged(i)) {
+            let (x, y) = board.pos(i);
+            return Some(GameMove::Reveal(x, y));
+        }
+        if let Some(&i) = safe.first() {
+            let (x, y) = board.pos(i);
+            return Some(GameMove::Flag(x, y));
+        }
+        if let Some(i) = (0..self.mines.len()).find(|&i| self.mines[i] && !flagged(i)) {
+            let (x, y) = board.pos(i);
+            return Some(GameMove::Flag(x, y));
+        }
+        None
+    }
+}
+
+/// Positions the player has flagged, which the revealed counts prove are not mines.
+pub fn contradicted_flags(game: &MinesweeperGame) -> Vec<(u32, u32)> {
+    let board = Board::from_game(game);
+    analyze(&board)
+        .safe
+        .into_iter()
+        .map(|i| board.pos(i))
+        .filter(|&(x, y)| game.is_flagged(x, y))
+        .collect()
+}
+
+/// Whether the player has to guess in the current position, and why.
+///
+/// Returns None when there is a provably safe move, or the frontier is too large to be sure.
+pub fn forced_guess(game: &MinesweeperGame) -> Option<ForcedGuessKind> {
+    if game.game_over() {
+        return None;
+    }
+    if game.revealed_count() == 0 {
+        return Some(ForcedGuessKind::Opening);
+    }
+
+    let board = Board::from_game(game);
+    analyze(&board).forced_guess(&board)
+}
+
+/// Find one position the player can be sure about, preferring a safe position over a mine.
+///
+/// The cheap deductions are repeated, building on the mines they prove, before trying
+/// elimination and then a full analysis. Once they prove a mine the player hasn't flagged,
+/// anything found after it could depend on it, so that mine is the hint instead, and the reason
+/// shown always holds on the board the player sees. Returns None when every move would be a
+/// guess.
+pub fn hint(game: &MinesweeperGame) -> Option<Hint> {
+    if game.game_over() || game.revealed_count() == 0 {
+        return None;
+    }
+
+    let mut board = Board::from_game(game);
+    let mut mine_hint = None;
+
+    while let Some(constraints) = constraints(&board) {
+        let mut progress = false;
+        for d in deductions(&board, &constraints) {
+            for &i in &d.cells {
+                let (x, y) = board.pos(i);
+                if !d.mine {
+                    return Some(Hint {
+                        x,
+                        y,
+                        mine: false,
+                        reason: d.reason,
+                    });
+                }
+                if board.cell(i) == Cell::Unknown {
+                    board.mark_mine(i);
+                    progress = true;
+                    if mine_hint.is_none() && !game.is_flagged(x, y) {
+                        mine_hint = Some(Hint {
+                            x,
+                            y,
+                            mine: true,
+                            reason: d.reason,
+                        });
+                    }
+                }
+            }
+        }
+
+        // anything found from here on could count on that mine, so point it out first
+        if mine_hint.is_some() {
+            return mine_hint;
+        }
+        if !progress {
+            break;
+        }
+    }
+
+    if let Some(constraints) = constraints(&board) {
+        let (safe, mines) = linear_deductions(&constraints);
+        let unflagged_mine = mines
+            .iter()
+            .map(|&i| board.pos(i))
+            .find(|&(x, y)| !game.is_flagged(x, y));
+        let found = match (safe.first(), unflagged_mine) {
+            (Some(&i), _) => Some((board.pos(i), false)),
+            (None, Some(pos)) => Some((pos, true)),
+            _ => None,
+        };
+        if let Some(((x, y), mine)) = found {
+            return Some(Hint {
+                x,
+                y,
+                mine,
+                reason: HintReason::Equations,
+            });
+        }
+    }
+
+    let analysis = analyze(&board);
+    if let Some(&i) = analysis.safe.first() {
+        let (x, y) = board.pos(i);
+        return Some(Hint {
+            x,
+            y,
+            mine: false,
+            reason: HintReason::Arrangements,
+        });
+    }
+
+    analysis
+        .mines
+        .iter()
+        .map(|&i| board.pos(i))
+        .find(|&(x, y)| !game.is_flagged(x, y))
+        .map(|(x, y)| Hint {
+            x,
+            y,
+            mine: true,
+            reason: HintReason::Arrangements,
+        })
+}
+
+/// Deduce everything that follows from a puzzle, and whether it has a unique solution.
+///
+/// Each round uses the cheapest kind of deduction that finds anything, and everything it finds
+/// is applied before the next round. A full analysis is only needed once the cheap deductions
+/// get stuck.
+pub fn solve_position(position: &Position) -> PositionSolution {
+    let mut board = Board::from_position(position);
+    let mut steps = Vec::new();
+
+    // keep the analysis that found nothing more, to judge whatever is left
+    let analysis = loop {
+        let Some(constraints) = constraints(&board) else {
+            break None;
+        };
+
+        let mut found = cheap_deductions(&board, &constraints);
+        if found.is_empty() {
+            let analysis = analyze(&board);
+            found = hints(
+                &board,
+                &analysis.safe,
+                &analysis.mines,
+                HintReason::Arrangements,
+            );
+            if found.is_empty() {
+                break Some(analysis);
+            }
+        }
+
+        for h in &found {
+            let i = board.index(h.x, h.y);
+            if h.mine {
+                board.mark_mine(i);
+            } else {
+                board.mark_safe(i);
+            }
+        }
+        steps.extend(found);
+    };
+
+    let uniqueness = match analysis {
+        Some(analysis) if analysis.consistent => {
+            if (0..board.len()).all(|i| board.cell(i) != Cell::Unknown) {
+                Uniqueness::Unique
+            } else if analysis.exact {
+                Uniqueness::Multiple
+            } else {
+                Uniqueness::Undecided
+            }
+        }
+        // deductions from contradictory counts mean nothing
+        _ => {
+            steps.clear();
+            Uniqueness::NoSolution
+        }
+    };
+
+    PositionSolution { steps, uniqueness }
+}
+
+/// Positions proven by the single and paired counts, or if there are none, by elimination.
+fn cheap_deductions(board: &Board, constraints: &[Constraint]) -> Vec<Hint> {
+    let mut found = Vec::new();
+    for d in deductions(board, constraints) {
+        let (safe, mines) = if d.mine {
+            (&[][..], &d.cells[..])
+        } else {
+            (&d.cells[..], &[][..])
+        };
+        found.extend(hints(board, safe, mines, d.reason));
+    }
+    if found.is_empty() {
+        let (safe, mines) = linear_deductions(constraints);
+        found = hints(board, &safe, &mines, HintReason::Equations);
+    }
+
+    // the same position may be proven by more than one count
+    let mut seen = BTreeSet::new();
+    found.retain(|h| seen.insert((h.x, h.y)));
+    found
+}
+
+fn hints(board: &Board, safe: &[usize], mines: &[usize], reason: HintReason) -> Vec<Hint> {
+    let safe = safe.iter().map(|&i| (i, false));
+    let mines = mines.iter().map(|&i| (i, true));
+    safe.chain(mines)
+        .map(|(i, mine)| {
+            let (x, y) = board.pos(i);
+            Hint { x, y, mine, reason }
+        })
+        .collect()
+}
+
+/// Judge revealing a position in the current state of the game, ignoring the player's flags.
+pub fn judge_reveal(game: &MinesweeperGame, x: u32, y: u32) -> RevealJudgement {
+    if game.revealed_count() == 0 {
+        return RevealJudgement::Forced(ForcedGuessKind::Opening);
+    }
+
+    let board = Board::from_game(game);
+    let i = board.index(x, y);
+
+    // cheap deductions can prove the position safe, but not that it's a blunder, since a
+    // subset or a full analysis may still prove it safe when they miss it
+    let mut other_safe = false;
+    if let Some(constraints) = constraints(&board) {
+        let (safe, _) = simple_deductions(&board, &constraints);
+        if safe.contains(&i) {
+            return RevealJudgement::Safe;
+        }
+        let (linear_safe, _) = linear_deductions(&constraints);
+        if linear_safe.contains(&i) {
+            return RevealJudgement::Safe;
+        }
+        other_safe = !safe.is_empty() || !linear_safe.is_empty();
+    }
+
+    let analysis = analyze(&board);
+    if analysis.safe.contains(&i) {
+        RevealJudgement::Safe
+    } else if other_safe || !analysis.safe.is_empty() {
+        RevealJudgement::Blunder
+    } else {
+        match analysis.forced_guess(&board) {
+            Some(kind) => RevealJudgement::Forced(kind),
+            None => RevealJudgement::Guess,
+        }
+    }
+}
+
+/// Result of analyzing every constraint on the board.
+pub struct Analysis {
+    /// Unknown positions which cannot be a mine.
+    pub safe: Vec<usize>,
+    /// Unknown positions which must be a mine.
+    pub mines: Vec<usize>,
+    /// Probability that each position is a mine, or None when revealed or known to be safe.
+    pub probabilities: Vec<Option<f64>>,
+    /// Whether every arrangement of the frontier was enumerated, so nothing certain was missed.
+    pub exact: bool,
+    /// Whether any arrangement of mines fits the counts at all.
+    pub consistent: bool,
+    /// Groups of positions with exactly two possible arrangements, which nothing else can resolve.
+    pub fifty_fifties: Vec<Vec<usize>>,
+}
+
+impl Analysis {
+    /// When there is no safe move, classify the guess that the player is forced to make.
+    pub fn forced_guess(&self, board: &Board) -> Option<ForcedGuessKind> {
+        if !self.exact || !self.safe.is_empty() {
+            return None;
+        }
+
+        let uncertain = |i: usize| self.probabilities[i].is_some_and(|p| p > 0.0 && p < 1.0);
+        if !(0..board.len()).any(uncertain) {
+            return None;
+        }
+
+        let any_revealed = (0..board.len()).any(|i| matches!(board.cell(i), Cell::Revealed(_)));
+        let frontier = (0..board.len()).any(|i| {
+            uncertain(i)
+                && board
+                    .neighbors(i)
+                    .any(|n| matches!(board.cell(n), Cell::Revealed(_)))
+        });
+
+        if !any_revealed {
+            Some(ForcedGuessKind::Opening)
+        } else if !self.fifty_fifties.is_empty() {
+            Some(ForcedGuessKind::FiftyFifty)
+        } else if !frontier {
+            Some(ForcedGuessKind::Isolated)
+        } else {
+            Some(ForcedGuessKind::Frontier)
+        }
+    }
+
+    /// Unknown position least likely to be a mine, among those allowed by the filter.
+    pub fn best_guess(&self, filter: impl Fn(usize) -> bool) -> Option<usize> {
+        let mut best: Option<(usize, f64)> = None;
+        for (i, p) in self.probabilities.iter().enumerate() {
+            if let Some(p) = *p
+                && p < 1.0
+                && filter(i)
+                && best.is_none_or(|(_, best_p)| p < best_p)
+            {
+                best = Some((i, p));
+            }
+        }
+        best.map(|(i, _)| i)
+    }
+}
+
+/// A revealed count, as the number of mines among a set of unknown positions.
+pub(super) struct Constraint {
+    /// Index of the revealed count.
+    pub(super) source: usize,
+    pub(super) cells: Vec<usize>,
+    pub(super) mines: usize,
+}
+
+/// Collect a constraint from each revealed count that still touches an unknown position.
+///
+/// Returns None if any count is already contradicted by the known mines.
+fn constraints(board: &Board) -> Option<Vec<Constraint>> {
+    let mut result = Vec::new();
+
+    for i in 0..board.len() {
+        if let Cell::Revealed(count) = board.cell(i) {
+            let mut cells = Vec::new();
+            let mut known_mines = 0;
+            for n in board.neighbors(i) {
+                match board.cell(n) {
+                    Cell::Unknown => cells.push(n),
+                    Cell::Mine => known_mines += 1,
+                    Cell::Safe | Cell::Revealed(_) => {}
+                }
+            }
+
+            let count = count as usize;
+            if known_mines > count || count - known_mines > cells.len() {
+                return None;
+            }
+            if !cells.is_empty() {
+                result.push(Constraint {
+                    source: i,
+                    cells,
+                    mines: count - known_mines,
+                });
+            }
+        }
+    }
+
+    Some(result)
+}
+
+/// Positions proven to all be mines, or all be safe, by one or two counts.
+struct Deduction {
+    cells: Vec<usize>,
+    mine: bool,
+    reason: HintReason,
+}
+
+/// Deductions from single counts, or if there are none, from one count whose positions are a
+/// subset of another's.
+fn deductions(board: &Board, constraints: &[Constraint]) -> Vec<Deduction> {
+    let mut result = Vec::new();
+
+    for c in constraints {
+        let source = board.pos(c.source);
+        if c.mines == 0 {
+            result.push(Deduction {
+                cells: c.cells.clone(),
+                mine: false,
+                reason: HintReason::CountSatisfied(source),
+            });
+        } else if c.mines == c.cells.len() {
+            result.push(Deduction {
+                cells: c.cells.clone(),
+                mine: true,
+                reason: HintReason::CountFull(source),
+            });
+        }
+    }
+
+    if result.is_empty() {
+        // only constraints that share a position can be subsets of each other
+        let mut by_cell: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (j, c) in constraints.iter().enumerate() {
+            for &cell in &c.cells {
+                by_cell.entry(cell).or_default().push(j);
+            }
+        }
+
+        for a in constraints {
+            for &j in &by_cell[&a.cells[0]] {
+                let b = &constraints[j];
+                if a.cells.len() >= b.cells.len() || !a.cells.iter().all(|c| b.cells.contains(c)) {
+                    continue;
+                }
+
+                let diff: Vec<usize> = b
+                    .cells
+                    .iter()
+                    .copied()
+                    .filter(|c| !a.cells.contains(c))
+                    .collect();
+                let diff_mines = b.mines.saturating_sub(a.mines);
+                let reason = HintReason::Subset(board.pos(a.source), board.pos(b.source));
+                if a.mines == b.mines {
+                    result.push(Deduction {
+                        cells: diff,
+                        mine: false,
+                        reason,
+                    });
+                } else if diff_mines == diff.len() {
+                    result.push(Deduction {
+                        cells: diff,
+                        mine: true,
+                        reason,
+                    });
+                }
+            }
+        }
+    }
+
+    result
+}
+
+/// Safe positions and mines from the cheap deductions.
+fn simple_deductions(board: &Board, constraints: &[Constraint]) -> (Vec<usize>, Vec<usize>) {
+    let mut safe = BTreeSet::new();
+    let mut mines = BTreeSet::new();
+
+    for d in deductions(board, constraints) {
+        if d.mine {
+            mines.extend(d.cells);
+        } else {
+            safe.extend(d.cells);
+        }
+    }
+
+    (safe.into_iter().collect(), mines.into_iter().collect())
+}
+
+/// Every arrangement of mines in one connected group of frontier positions.
+struct Solutions {
+    /// Number of arrangements, by number of mines in the group.
+    counts: Vec<f64>,
+    /// Number of arrangements where each position is a mine, by number of mines in the group.
+    cell_counts: Vec<Vec<f64>>,
+}
+
+/// Backtracking search over the positions of one frontier component.
+struct Search {
+    cell_constraints: Vec<Vec<usize>>,
+    needed: Vec<usize>,
+    open: Vec<usize>,
+    assigned: Vec<bool>,
+    budget: usize,
+    solutions: Solutions,
+}
+
+impl Search {
+    fn assign(&mut self, j: usize, mine: bool) -> bool {
+        let mut ok = true;
+        for &c in &self.cell_constraints[j] {
+            self.open[c] -= 1;
+            if mine {
+                if self.needed[c] == 0 {
+                    ok = false;
+                }
+                self.needed[c] = self.needed[c].wrapping_sub(1);
+            }
+            if self.needed[c] > self.open[c] {
+                ok = false;
+            }
+        }
+        self.assigned[j] = mine;
+        ok
+    }
+
+    fn unassign(&mut self, j: usize, mine: bool) {
+        for &c in &self.cell_constraints[j] {
+            self.open[c] += 1;
+            if mine {
+                self.needed[c] = self.needed[c].wrapping_add(1);
+            }
+        }
+        self.assigned[j] = false;
+    }
+
+    /// Returns false if the search ran out of budget.
+    fn search(&mut self, j: usize, mines: usize) -> bool {
+        if self.budget == 0 {
+            return false;
+        }
+        self.budget -= 1;
+
+        if j == self.assigned.len() {
+            self.solutions.counts[mines] += 1.0;
+            for (l, &mine) in self.assigned.iter().enumerate() {
+                if mine {
+                    self.solutions.cell_counts[l][mines] += 1.0;
+                }
+            }
+            return true;
+        }
+
+        for mine in [false, true] {
+            let ok = self.assign(j, mine);
+            let complete = !ok || self.search(j + 1, mines + mine as usize);
+            self.unassign(j, mine);
+            if !complete {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+fn enumerate(cells: &[usize], constraints: Vec<&Constraint>) -> Option<Solutions> {
+    let local: HashMap<usize, usize> = cells.iter().enumerate().map(|(l, &c)| (c, l)).collect();
+
+    let mut cell_constraints = vec![Vec::new(); cells.len()];
+    for (j, c) in constraints.iter().enumerate() {
+        for cell in &c.cells {
+            cell_constraints[local[cell]].push(j);
+        }
+    }
+
+    let mut search = Search {
+        needed: constraints.iter().map(|c| c.mines).collect(),
+        open: constraints.iter().map(|c| c.cells.len()).collect(),
+        cell_constraints,
+        assigned: vec![false; cells.len()],
+        budget: ENUMERATION_BUDGET,
+        solutions: Solutions {
+            counts: vec![0.0; cells.len() + 1],
+            cell_counts: vec![vec![0.0; cells.len() + 1]; cells.len()],
+        },
+    };
+
+    if search.search(0, 0) {
+        Some(search.solutions)
+    } else {
+        None
+    }
+}
+
+/// Split the frontier into groups of positions that share constraints.
+fn components(constraints: &[Constraint]) -> Vec<(Vec<usize>, Vec<&Constraint>)> {
+    let mut by_cell: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (j, c) in constraints.iter().enumerate() {
+        for &cell in &c.cells {
+            by_cell.entry(cell).or_default().push(j);
+        }
+    }
+
+    let mut result = Vec::new();
+    let mut visited = vec![false; constraints.len()];
+
+    for start in 0..constraints.len() {
+        if visited[start] {
+            continue;
+        }
+        visited[start] = true;
+
+        let mut cells = Vec::new();
+        let mut members = Vec::new();
+        let mut queue = vec![start];
+        let mut seen_cells = BTreeSet::new();
+
+        while let Some(j) = queue.pop() {
+            members.push(&constraints[j]);
+            for &cell in &constraints[j].cells {
+                if seen_cells.insert(cell) {
+                    cells.push(cell);
+                    for &k in &by_cell[&cell] {
+                        if !visited[k] {
+                            visited[k] = true;
+                            queue.push(k);
+                        }
+                    }
+                }
+            }
+        }
+
+        result.push((cells, members));
+    }
+
+    result
+}
+
+fn convolve(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let mut result = vec![0.0; a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            result[i + j] += x * y;
+        }
+    }
+
+    // rescale to keep the numbers in range, since only ratios matter
+    let max = result.iter().copied().fold(0.0, f64::max);
+    if max > 0.0 {
+        result.iter_mut().for_each(|v| *v /= max);
+    }
+    result
+}
+
+fn convolve_feasible(a: &[bool], b: &[bool]) -> Vec<bool> {
+    let mut result = vec![false; a.len() + b.len() - 1];
+    for (i, &x) in a.iter().enumerate() {
+        for (j, &y) in b.iter().enumerate() {
+            result[i + j] |= x && y;
+        }
+    }
+    result
+}
+
+/// Natural log of the binomial coefficient, using a table of log factorials.
+fn ln_binomial(ln_fact: &[f64], n: usize, k: usize) -> f64 {
+    ln_fact[n] - ln_fact[k] - ln_fact[n - k]
+}
+
+/// Analyze the whole board, finding every certain position and the probability of the rest.
+///
+/// Frontier components too large to enumerate are treated like positions away from the frontier,
+/// so their probabilities are approximate, but any certain positions are still correct.
+pub fn analyze(board: &Board) -> Analysis {
+    let mut probabilities: Vec<Option<f64>> = (0..board.len())
+        .map(|i| match board.cell(i) {
+            Cell::Unknown => Some(0.0),
+            Cell::Mine => Some(1.0),
+            Cell::Safe | Cell::Revealed(_) => None,
+        })
+        .collect();
+    let mut safe = Vec::new();
+    let mut mines = Vec::new();
+
+    let unknown_count = (0..board.len())
+        .filter(|&i| board.cell(i) == Cell::Unknown)
+        .count();
+    let known_mines = (0..board.len())
+        .filter(|&i| board.cell(i) == Cell::Mine)
+        .count();
+
+    let Some(constraints) = constraints(board) else {
+        return fallback_analysis(board, probabilities, unknown_count, known_mines);
+    };
+    if board.mines().is_some_and(|mines| known_mines > mines) {
+        return fallback_analysis(board, probabilities, unknown_count, known_mines);
+    }
+
+    // enumerate each component, leaving any that are too large in with the rest of the board
+    let mut solved = Vec::new();
+    let mut exact = true;
+    let mut in_component = vec![false; board.len()];
+    for (cells, members) in components(&constraints) {
+        if let Some(solutions) = enumerate(&cells, members) {
+            for &c in &cells {
+                in_component[c] = true;
+            }
+            solved.push((cells, solutions));
+        } else {
+            exact = false;
+        }
+    }
+
+    let rest: Vec<usize> = (0..board.len())
+        .filter(|&i| board.cell(i) == Cell::Unknown && !in_component[i])
+        .collect();
+    let rest_count = rest.len();
+
+    // the total number of mines constrains how many can be in the rest of the board
+    let use_total = board.mines().is_some();
+    let mines_left = board.mines().unwrap_or(0).saturating_sub(known_mines);
+
+    let mut ln_fact = vec![0.0; rest_count + 1];
+    for n in 1..=rest_count {
+        ln_fact[n] = ln_fact[n - 1] + (n as f64).ln();
+    }
+
+    let valid_total = |t: usize| !use_total || (t <= mines_left && mines_left - t <= rest_count);
+    let ln_weight = |t: usize| {
+        if use_total {
+            ln_binomial(&ln_fact, rest_count, mines_left - t)
+        } else {
+            0.0
+        }
+    };
+
+    let counts: Vec<Vec<f64>> = solved.iter().map(|(_, s)| s.counts.clone()).collect();
+    let feasible: Vec<Vec<bool>> = counts
+        .iter()
+        .map(|c| c.iter().map(|&v| v > 0.0).collect())
+        .collect();
+
+    // distributions of the number of mines in all components before/after each one
+    let mut prefix = vec![vec![1.0]];
+    let mut prefix_feasible = vec![vec![true]];
+    for c in 0..solved.len() {
+        prefix.push(convolve(&prefix[c], &counts[c]));
+        prefix_feasible.push(convolve_feasible(&prefix_feasible[c], &feasible[c]));
+    }
+    let mut suffix = vec![vec![1.0]; solved.len() + 1];
+    let mut suffix_feasible = vec![vec![true]; solved.len() + 1];
+    for c in (0..solved.len()).rev() {
+        suffix[c] = convolve(&suffix[c + 1], &counts[c]);
+        suffix_feasible[c] = convolve_feasible(&suffix_feasible[c + 1], &feasible[c]);
+    }
+
+    let total = &prefix[solved.len()];
+    let total_feasible = &prefix_feasible[solved.len()];
+
+    let max_ln_weight = (0..total.len())
+        .filter(|&t| total_feasible[t] && valid_total(t))
+        .map(ln_weight)
+        .fold(f64::NEG_INFINITY, f64::max);
+    if max_ln_weight == f64::NEG_INFINITY {
+        return fallback_analysis(board, probabilities, unknown_count, known_mines);
+    }
+    let weight = |t: usize| {
+        if valid_total(t) {
+            (ln_weight(t) - max_ln_weight).exp()
+        } else {
+            0.0
+        }
+    };
+
+    // positions in each component
+    let mut fifty_fifties = Vec::new();
+    for (c, (cells, solutions)) in solved.iter().enumerate() {
+        if is_fifty_fifty(board, cells, solutions) {
+            fifty_fifties.push(cells.clone());
+        }
+
+        let others = convolve(&prefix[c], &suffix[c + 1]);
+        let others_feasible = convolve_feasible(&prefix_feasible[c], &suffix_feasible[c + 1]);
+
+        let k_weight: Vec<f64> = (0..solutions.counts.len())
+            .map(|k| (0..others.len()).map(|s| others[s] * weight(k + s)).sum())
+            .collect();
+        let k_feasible: Vec<bool> = (0..solutions.counts.len())
+            .map(|k| (0..others.len()).any(|s| others_feasible[s] && valid_total(k + s)))
+            .collect();
+
+        let total_weight: f64 = (0..solutions.counts.len())
+            .map(|k| solutions.counts[k] * k_weight[k])
+            .sum();
+
+        for (l, &cell) in cells.iter().enumerate() {
+            let cell_counts = &solutions.cell_counts[l];
+            let mut cell_weight = 0.0;
+            let mut can_be_mine = false;
+            let mut can_be_safe = false;
+            for k in 0..solutions.counts.len() {
+                cell_weight += cell_counts[k] * k_weight[k];
+                if k_feasible[k] {
+                    can_be_mine |= cell_counts[k] > 0.0;
+                    can_be_safe |= solutions.counts[k] > cell_counts[k];
+                }
+            }
+
+            probabilities[cell] = Some(if total_weight > 0.0 {
+                cell_weight / total_weight
+            } else {
+                0.5
+            });
+            if !can_be_mine {
+                safe.push(cell);
+                probabilities[cell] = Some(0.0);
+            } else if !can_be_safe {
+                mines.push(cell);
+                probabilities[cell] = Some(1.0);
+            }
+        }
+    }
+
+    // positions in the rest of the board all share the same probability
+    if rest_count > 0 {
+        let mut rest_weight = 0.0;
+        let mut total_weight = 0.0;
+        let mut can_be_mine = !use_total;
+        let mut can_be_safe = !use_total;
+        for t in 0..total.len() {
+            let w = total[t] * weight(t);
+            total_weight += w;
+            if use_total && t <= mines_left {
+                let left = mines_left - t;
+                rest_weight += w * left as f64 / rest_count as f64;
+                if total_feasible[t] && valid_total(t) {
+                    can_be_mine |= left > 0;
+                    can_be_safe |= left < rest_count;
+                }
+            }
+        }
+
+        let p = if !use_total {
+            density(board, unknown_count, known_mines)
+        } else if total_weight > 0.0 {
+            rest_weight / total_weight
+        } else {
+            0.5
+        };
+
+        for &i in &rest {
+            probabilities[i] = Some(p);
+            if !can_be_mine {
+                safe.push(i);
+                probabilities[i] = Some(0.0);
+            } else if !can_be_safe {
+                mines.push(i);
+                probabilities[i] = Some(1.0);
+            }
+        }
+    }
+
+    safe.sort_unstable();
+    mines.sort_unstable();
+
+    Analysis {
+        safe,
+        mines,
+        probabilities,
+        exact,
+        consistent: true,
+        fifty_fifties,
+    }
+}
+
+/// Whether a component has exactly two arrangements, with every position a mine in one of them,
+/// and no unknown neighbors outside the component that could ever tell the two apart.
+fn is_fifty_fifty(board: &Board, cells: &[usize], solutions: &Solutions) -> bool {
+    let total: f64 = solutions.counts.iter().sum();
+    if total != 2.0 {
+        return false;
+    }
+    if !solutions
+        .cell_counts
+        .iter()
+        .all(|c| c.iter().sum::<f64>() == 1.0)
+    {
+        return false;
+    }
+
+    cells.iter().all(|&i| {
+        board
+            .neighbors(i)
+            .all(|n| board.cell(n) != Cell::Unknown || cells.contains(&n))
+    })
+}
+
+/// Average chance of a mine among the unknown positions.
+fn density(board: &Board, unknown_count: usize, known_mines: usize) -> f64 {
+    match board.mines() {
+        _ if unknown_count == 0 => 0.0,
+        Some(mines) => (mines.saturating_sub(known_mines) as f64 / unknown_count as f64).min(1.0),
+        None => 0.5,
+    }
+}
+
+/// Analysis of a board whose counts contradict each other, where nothing can be certain.
+fn fallback_analysis(
+    board: &Board,
+    mut probabilities: Vec<Option<f64>>,
+    unknown_count: usize,
+    known_mines: usize,
+) -> Analysis {
+    let p = density(board, unknown_count, known_mines);
+    for (i, probability) in probabilities.iter_mut().enumerate() {
+        if board.cell(i) == Cell::Unknown {
+            *probability = Some(p);
+        }
+    }
+
+    Analysis {
+        safe: Vec::new(),
+        mines: Vec::new(),
+        probabilities,
+        exact: false,
+        consistent: false,
+        fifty_fifties: Vec::new(),
+    }
+}
@@ -0,0 +1,230 @@
+use super::constraint::Constraint;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// A linear equation over unknown positions, each of which is 0 (safe) or 1 (a mine).
+///
+/// Terms are kept sorted by position, without zero coefficients.
+#[derive(Clone)]
+struct Equation {
+    terms: Vec<(usize, i64)>,
+    total: i64,
+}
+
+impl Equation {
+    fn from_constraint(c: &Constraint) -> Self {
+        let mut terms: Vec<(usize, i64)> = c.cells.iter().map(|&cell| (cell, 1)).collect();
+        terms.sort_unstable();
+        Equation {
+            terms,
+            total: c.mines as i64,
+        }
+    }
+
+    fn coefficient(&self, cell: usize) -> i64 {
+        match self.terms.binary_search_by_key(&cell, |&(c, _)| c) {
+            Ok(t) => self.terms[t].1,
+            Err(_) => 0,
+        }
+    }
+
+    /// `a * self - b * other`, with common factors divided out, or None if it overflows.
+    fn combine(&self, a: i64, other: &Equation, b: i64) -> Option<Equation> {
+        let mut terms = Vec::with_capacity(self.terms.len() + other.terms.len());
+        let (mut i, mut j) = (0, 0);
+        while i < self.terms.len() || j < other.terms.len() {
+            let left = self.terms.get(i).copied();
+            let right = other.terms.get(j).copied();
+            let (cell, x, y) = match (left, right) {
+                (Some((cl, x)), Some((cr, _))) if cl < cr => {
+                    i += 1;
+                    (cl, x, 0)
+                }
+                (Some((cl, x)), Some((cr, y))) if cl == cr => {
+                    i += 1;
+                    j += 1;
+                    (cl, x, y)
+                }
+                (_, Some((cr, y))) => {
+                    j += 1;
+                    (cr, 0, y)
+                }
+                (Some((cl, x)), None) => {
+                    i += 1;
+                    (cl, x, 0)
+                }
+                (None, None) => unreachable!(),
+            };
+
+            let coefficient = a.checked_mul(x)?.checked_sub(b.checked_mul(y)?)?;
+            if coefficient != 0 {
+                terms.push((cell, coefficient));
+            }
+        }
+
+        let total = a
+            .checked_mul(self.total)?
+            .checked_sub(b.checked_mul(other.total)?)?;
+        let mut result = Equation { terms, total };
+        result.normalize();
+        Some(result)
+    }
+
+    /// Divide out the greatest common factor, so coefficients stay small.
+    fn normalize(&mut self) {
+        let divisor = self
+            .terms
+            .iter()
+            .fold(self.total.unsigned_abs(), |g, &(_, c)| {
+                gcd(g, c.unsigned_abs())
+            });
+        if divisor > 1 {
+            let divisor = divisor as i64;
+            for term in &mut self.terms {
+                term.1 /= divisor;
+            }
+            self.total /= divisor;
+        }
+    }
+
+    /// Positions whose value is forced because the total sits at one end of the range the
+    /// equation can reach, given that every position is 0 or 1.
+    fn forced(&self, safe: &mut BTreeSet<usize>, mines: &mut BTreeSet<usize>) {
+        let low: i64 = self.terms.iter().map(|&(_, c)| c.min(0)).sum();
+        let high: i64 = self.terms.iter().map(|&(_, c)| c.max(0)).sum();
+
+        // at the low end every positive term is 0 and every negative term is 1, and the reverse
+        // at the high end
+        let positive_mines = if self.total == low {
+            false
+        } else if self.total == high {
+            true
+        } else {
+            return;
+        };
+
+        for &(cell, c) in &self.terms {
+            if (c > 0) == positive_mines {
+                mines.insert(cell);
+            } else {
+                safe.insert(cell);
+            }
+        }
+    }
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Safe positions and mines found by Gaussian elimination of the constraints.
+///
+/// Every count is an equation over its unknown neighbors. The system is reduced with exact
+/// integer arithmetic, and each reduced equation is checked against the 0/1 bounds of its
+/// positions. Unlike enumeration the cost only grows polynomially with the frontier, and the
+/// equations are sparse, so it stays fast on very large boards, though it can miss deductions
+/// that need the arrangements themselves. Finds nothing if the numbers grow too large.
+pub(super) fn linear_deductions(constraints: &[Constraint]) -> (Vec<usize>, Vec<usize>) {
+    let mut safe = BTreeSet::new();
+    let mut mines = BTreeSet::new();
+
+    if let Some(reduced) = reduce(constraints) {
+        for equation in &reduced {
+            equation.forced(&mut safe, &mut mines);
+        }
+    }
+
+    (safe.into_iter().collect(), mines.into_iter().collect())
+}
+
+/// Reduce the constraints to equations that each have a pivot position, which appears in no
+/// other equation.
+fn reduce(constraints: &[Constraint]) -> Option<Vec<Equation>> {
+    // equations by pivot position, with the equations using each other position
+    let mut pivots: BTreeMap<usize, Equation> = BTreeMap::new();
+    let mut uses: HashMap<usize, BTreeSet<usize>> = HashMap::new();
+
+    for c in constraints {
+        let mut equation = Equation::from_constraint(c);
+
+        // remove every existing pivot position from the new equation
+        let cells: Vec<usize> = equation.terms.iter().map(|&(cell, _)| cell).collect();
+        for cell in cells {
+            let b = equation.coefficient(cell);
+            if let Some(pivot) = pivots.get(&cell)
+                && b != 0
+            {
+                equation = equation.combine(pivot.coefficient(cell), pivot, b)?;
+            }
+        }
+
+        // an equation with nothing left is either redundant or contradicts the others
+        let Some(&(pivot_cell, a)) = equation.terms.first() else {
+            continue;
+        };
+
+        // then remove the new pivot position from the existing equations
+        for p in uses.remove(&pivot_cell).unwrap_or_default() {
+            let row = &pivots[&p];
+            let b = row.coefficient(pivot_cell);
+            if b == 0 {
+                continue;
+            }
+            let updated = row.combine(a, &equation, b)?;
+            for &(cell, _) in &updated.terms {
+                if cell != p {
+                    uses.entry(cell).or_default().insert(p);
+                }
+            }
+            pivots.insert(p, updated);
+        }
+
+        for &(cell, _) in &equation.terms[1..] {
+            uses.entry(cell).or_default().insert(pivot_cell);
+        }
+        pivots.insert(pivot_cell, equation);
+    }
+
+    Some(pivots.into_values().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn constraint(cells: &[usize], mines: usize) -> Constraint {
+        Constraint {
+            source: 0,
+            cells: cells.to_vec(),
+            mines,
+        }
+    }
+
+    #[test]
+    fn one_two_one() {
+        // counts 1, 2, 1 below a row of three unknown positions
+        let constraints = [
+            constraint(&[0, 1], 1),
+            constraint(&[0, 1, 2], 2),
+            constraint(&[1, 2], 1),
+        ];
+        assert_eq!(linear_deductions(&constraints), (vec![1], vec![0, 2]));
+    }
+
+    #[test]
+    fn undetermined_pair() {
+        let constraints = [constraint(&[0, 1], 1), constraint(&[0, 1], 1)];
+        assert_eq!(linear_deductions(&constraints), (vec![], vec![]));
+    }
+
+    #[test]
+    fn combine_divides_out_common_factors() {
+        let a = Equation::from_constraint(&constraint(&[0, 1, 2], 2));
+        let b = Equation::from_constraint(&constraint(&[0, 1], 1));
+        let combined = a.combine(2, &b, 2).unwrap();
+        assert_eq!(combined.terms, vec![(2, 1)]);
+        assert_eq!(combined.total, 1);
+    }
+}
@@ -0,0 +1,201 @@
+use super::{ConstraintSolver, Solver};
+use crate::game::{GameConfig, GameMove, GameState, GridState, MinesweeperGame};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex, PoisonError};
+
+/// Games played with each candidate when learning the opening for a config.
+const LEARNING_GAMES: usize = 500;
+
+/// Seed for the layouts played when learning an opening, so every run learns the same one.
+const LEARNING_SEED: u64 = 0;
+
+/// Where a solver makes its first click.
+///
+/// Corner is the default. Measured by `minesweeper-bench --openings`, it opens up the board far
+/// more often than the other candidates on every preset, and none of them wins measurably more
+/// often.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum OpeningPolicy {
+    Center,
+    #[default]
+    Corner,
+    /// Middle of the top edge.
+    Edge,
+    /// Whichever candidate position wins most often in simulated games, see `learned_opening`.
+    Learned,
+}
+
+impl OpeningPolicy {
+    pub const ALL: [OpeningPolicy; 4] = [
+        OpeningPolicy::Center,
+        OpeningPolicy::Corner,
+        OpeningPolicy::Edge,
+        OpeningPolicy::Learned,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            OpeningPolicy::Center => "Center",
+            OpeningPolicy::Corner => "Corner",
+            OpeningPolicy::Edge => "Edge",
+            OpeningPolicy::Learned => "Learned",
+        }
+    }
+
+    /// Position of the first click for a game with this config.
+    pub fn position(self, config: GameConfig) -> (u32, u32) {
+        match self {
+            OpeningPolicy::Center => (config.width() / 2, config.height() / 2),
+            OpeningPolicy::Corner => (0, 0),
+            OpeningPolicy::Edge => (config.width() / 2, 0),
+            OpeningPolicy::Learned => learned_opening(config),
+        }
+    }
+}
+
+/// How often a first click at one position opened up the board, and went on to win.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct OpeningStats {
+    pub position: (u32, u32),
+    pub games: usize,
+    /// Games where the first click revealed an empty position, opening up its neighbors.
+    pub openings: usize,
+    pub wins: usize,
+}
+
+impl OpeningStats {
+    pub fn opening_rate(&self) -> f64 {
+        self.openings as f64 / self.games as f64
+    }
+
+    pub fn win_rate(&self) -> f64 {
+        self.wins as f64 / self.games as f64
+    }
+}
+
+/// Positions worth trying as the first click: a corner, an edge, the center, and one position in
+/// from the corner and the edge, without repeats on small boards.
+pub fn candidate_openings(config: GameConfig) -> Vec<(u32, u32)> {
+    let (w, h) = (config.width(), config.height());
+    let mut candidates = Vec::new();
+    for position in [
+        (0, 0),
+        (1.min(w - 1), 1.min(h - 1)),
+        (w / 2, 0),
+        (w / 2, 1.min(h - 1)),
+        (w / 2, h / 2),
+    ] {
+        if !candidates.contains(&position) {
+            candidates.push(position);
+        }
+    }
+    candidates
+}
+
+/// Play seeded games with every first click position, finishing each with the constraint solver.
+///
+/// Every position plays the same layouts, so differences come from the position rather than luck.
+pub fn study_openings(
+    config: GameConfig,
+    positions: &[(u32, u32)],
+    games: usize,
+    seed: u64,
+) -> Vec<OpeningStats> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut solver = ConstraintSolver::default();
+    let mut stats: Vec<OpeningStats> = positions
+        .iter()
+        .map(|&position| OpeningStats {
+            position,
+            games: 0,
+            openings: 0,
+            wins: 0,
+        })
+        .collect();
+
+    for _ in 0..games {
+        let mines = MinesweeperGame::with_rng(config, &mut rng).mine_positions();
+        for s in &mut stats {
+            let mut game = MinesweeperGame::with_mines(config, &mines);
+            solver.reset();
+
+            let (x, y) = s.position;
+            game.reveal(x, y);
+            if game.peek_at(x, y, false) == GridState::Empty {
+                s.openings += 1;
+            }
+
+            while !game.game_over() {
+                let m = solver.next_move(&game);
+                let before = (game.revealed_count(), game.flagged_count());
+                game.make_move(m.game_move);
+                if m.game_move == GameMove::NoOp
+                    || before == (game.revealed_count(), game.flagged_count())
+                {
+                    break;
+                }
+            }
+
+            s.games += 1;
+            if game.state() == GameState::Completed {
+                s.wins += 1;
+            }
+        }
+    }
+
+    stats
+}
+
+/// Opening learned for each config, or None while the study is still running.
+type Learned = HashMap<GameConfig, Option<(u32, u32)>>;
+
+static LEARNED: LazyLock<Mutex<Learned>> = LazyLock::new(Default::default);
+
+/// Best candidate first click for a config, by win rate and then opening rate in simulated games.
+///
+/// The first time a config is asked for, its games are played on another thread, so the first
+/// move doesn't stall, and the corner is used until they're done. See `learn_opening` to wait for
+/// the result instead.
+pub fn learned_opening(config: GameConfig) -> (u32, u32) {
+    let mut learned = LEARNED.lock().unwrap_or_else(PoisonError::into_inner);
+    match learned.get(&config) {
+        Some(Some(position)) => *position,
+        Some(None) => OpeningPolicy::Corner.position(config),
+        None => {
+            learned.insert(config, None);
+            std::thread::spawn(move || {
+                let best = best_opening(config);
+                LEARNED
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .insert(config, Some(best));
+            });
+            OpeningPolicy::Corner.position(config)
+        }
+    }
+}
+
+/// Learn the opening for a config straight away, for `learned_opening` to use from then on.
+pub fn learn_opening(config: GameConfig) -> (u32, u32) {
+    let best = best_opening(config);
+    LEARNED
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(config, Some(best));
+    best
+}
+
+/// Play `LEARNING_GAMES` seeded games with each candidate.
+fn best_opening(config: GameConfig) -> (u32, u32) {
+    study_openings(
+        config,
+        &candidate_openings(config),
+        LEARNING_GAMES,
+        LEARNING_SEED,
+    )
+    .iter()
+    .max_by_key(|s| (s.wins, s.openings))
+    .map_or((0, 0), |s| s.position)
+}
@@ -0,0 +1,44 @@
+use super::{Solver, SolverMove};
+use crate::game::{GameMove, GridState, MinesweeperGame};
+use rand::SeedableRng;
+use rand::prelude::IndexedRandom;
+use rand::rngs::StdRng;
+
+/// Baseline solver, which reveals a random unrevealed position every move.
+pub struct RandomSolver {
+    rng: StdRng,
+}
+
+impl Default for RandomSolver {
+    fn default() -> Self {
+        RandomSolver {
+            rng: StdRng::from_os_rng(),
+        }
+    }
+}
+
+impl Solver for RandomSolver {
+    fn set_seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    fn propose_move(&mut self, game: &MinesweeperGame) -> SolverMove {
+        if game.game_over() {
+            return SolverMove::NO_OP;
+        }
+
+        let mut unrevealed = Vec::new();
+        for y in 0..game.height() {
+            for x in 0..game.width() {
+                if game.peek_at(x, y, false) == GridState::Unrevealed {
+                    unrevealed.push((x, y));
+                }
+            }
+        }
+
+        match unrevealed.choose(&mut self.rng) {
+            Some(&(x, y)) => SolverMove::guess(GameMove::Reveal(x, y)),
+            None => SolverMove::NO_OP,
+        }
+    }
+}
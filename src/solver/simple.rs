@@ -0,0 +1,156 @@
+use super::{ForcedGuess, OpeningPolicy, Solver, SolverMove};
+use crate::game::{GameMove, GridState, MinesweeperGame};
+use std::collections::HashSet;
+
+/// Simple rule-based solver.
+///
+/// Only looks at one count at a time, and gives up when no single count determines a move.
+/// The player's flags are not trusted, so the solver remembers the mines it has proven itself.
+#[derive(Default)]
+pub struct SimpleSolver {
+    mines: HashSet<(u32, u32)>,
+    opening: OpeningPolicy,
+}
+
+impl Solver for SimpleSolver {
+    fn reset(&mut self) {
+        self.mines.clear();
+    }
+
+    fn set_opening(&mut self, opening: OpeningPolicy) {
+        self.opening = opening;
+    }
+
+    fn observe(&mut self, game: &MinesweeperGame) {
+        // nothing proven yet for a new game
+        if game.revealed_count() == 0 {
+            self.mines.clear();
+        }
+    }
+
+    fn propose_move(&mut self, game: &MinesweeperGame) -> SolverMove {
+        // no moves possible
+        if game.game_over() {
+            return SolverMove::NO_OP;
+        }
+
+        let w = game.width();
+        let h = game.height();
+
+        // special case for first move: nothing to go on, so follow the opening policy
+        if game.revealed_count() == 0 {
+            let (x, y) = self.opening.position(game.config());
+            return SolverMove::forced(GameMove::Reveal(x, y), ForcedGuess::opening(game));
+        }
+
+        // when every mine has been proven, or every unresolved square must be a mine, resolve the rest
+        let mut unresolved = Vec::new();
+        for y in 0..h {
+            for x in 0..w {
+                let state = game.peek_at(x, y, false);
+                if is_unresolved(state) && !self.mines.contains(&(x, y)) {
+                    unresolved.push((x, y));
+                }
+            }
+        }
+        let mines_unproven = game.config().mines().saturating_sub(self.mines.len());
+        if mines_unproven == 0 {
+            if let Some(m) = self.resolve(game, &unresolved, false) {
+                return SolverMove::certain(m);
+            }
+        } else if unresolved.len() == mines_unproven
+            && let Some(m) = self.resolve(game, &unresolved, true)
+        {
+            return SolverMove::certain(m);
+        }
+
+        // check for any logically consistent moves around a square with a count
+        for y in 0..h {
+            for x in 0..w {
+                let state = game.peek_at(x, y, false);
+                if let GridState::Count(count) = state
+                    && let Some(m) = self.logical_move_around_count(x, y, count, game)
+                {
+                    return SolverMove::certain(m);
+                }
+            }
+        }
+
+        // TODO: now we need to guess...
+
+        SolverMove::NO_OP
+    }
+}
+
+impl SimpleSolver {
+    fn logical_move_around_count(
+        &mut self,
+        x: u32,
+        y: u32,
+        count: u8,
+        game: &MinesweeperGame,
+    ) -> Option<GameMove> {
+        // count the unresolved positions around a point, as well as any mines already proven
+        let mut mine_count = 0;
+        let mut unresolved = Vec::new();
+
+        for (nx, ny) in game.neighbors(x, y) {
+            let neighbor_state = game.peek_at(nx, ny, false);
+            if self.mines.contains(&(nx, ny)) {
+                mine_count += 1;
+            } else if is_unresolved(neighbor_state) {
+                unresolved.push((nx, ny));
+            } else if !matches!(neighbor_state, GridState::Empty | GridState::Count(_)) {
+                return None;
+            }
+        }
+
+        if unresolved.is_empty() {
+            return None;
+        }
+
+        // if the count on this point matches the number of unresolved neighbors plus known mines, then they are all mines
+        if count as usize == unresolved.len() + mine_count {
+            return self.resolve(game, &unresolved, true);
+        }
+
+        // if the count on this point already matches the number of known mines around it, then the rest are safe
+        if count as usize == mine_count {
+            return self.resolve(game, &unresolved, false);
+        }
+
+        None
+    }
+
+    /// Move towards resolving positions known to all be mines, or all be safe.
+    ///
+    /// Mines are remembered and flagged, while safe positions are revealed, after removing any
+    /// flag the player put there. Returns None when there is nothing left to do.
+    fn resolve(
+        &mut self,
+        game: &MinesweeperGame,
+        positions: &[(u32, u32)],
+        mines: bool,
+    ) -> Option<GameMove> {
+        if mines {
+            self.mines.extend(positions);
+            positions
+                .iter()
+                .find(|&&(x, y)| !game.is_flagged(x, y))
+                .map(|&(x, y)| GameMove::Flag(x, y))
+        } else {
+            positions.first().map(|&(x, y)| {
+                if game.is_flagged(x, y) {
+                    GameMove::Flag(x, y)
+                } else {
+                    GameMove::Reveal(x, y)
+                }
+            })
+        }
+    }
+}
+
+/// Whether a position may still be hiding a mine, as far as the counts are concerned.
+fn is_unresolved(state: GridState) -> bool {
+    matches!(state, GridState::Unrevealed | GridState::Flagged)
+}
@@ -1,5 +1,12 @@
-use egui::{pos2, vec2, Button, Direction, Image, ImageSource, Rect, Response, Ui, Vec2};
+use crate::theme::Theme;
+use crate::vector::VectorStyle;
+use egui::load::TexturePoll;
+use egui::{
+    Button, Color32, Direction, Image, Mesh, Rect, Response, Sense, TextureId, TextureOptions, Ui,
+    Vec2, vec2,
+};
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum SpriteType {
     Digit0,
     Digit1,
@@ -11,6 +18,7 @@ pub enum SpriteType {
     Digit7,
     Digit8,
     Digit9,
+    DigitMinus,
 
     FaceSmileyUp,
     FaceSmileyDown,
@@ -35,9 +43,26 @@ pub enum SpriteType {
     Block6,
     Block7,
     Block8,
+    /// Counts above 8, for variant boards. Spritesheets don't have these, so they're drawn as
+    /// `BlockEmptyDown` with the number painted over it.
+    BlockNumber(u8),
 }
 
 impl SpriteType {
+    #[rustfmt::skip]
+    pub const ALL: [SpriteType; 32] = [
+        SpriteType::Digit0, SpriteType::Digit1, SpriteType::Digit2, SpriteType::Digit3,
+        SpriteType::Digit4, SpriteType::Digit5, SpriteType::Digit6, SpriteType::Digit7,
+        SpriteType::Digit8, SpriteType::Digit9, SpriteType::DigitMinus,
+        SpriteType::FaceSmileyUp, SpriteType::FaceSmileyDown, SpriteType::FaceRuhRoh,
+        SpriteType::FaceCool, SpriteType::FaceXXX,
+        SpriteType::BlockEmptyUp, SpriteType::BlockEmptyDown, SpriteType::BlockFlag,
+        SpriteType::BlockQuestionUp, SpriteType::BlockQuestionDown, SpriteType::BlockMine,
+        SpriteType::BlockMineRed, SpriteType::BlockMineX,
+        SpriteType::Block1, SpriteType::Block2, SpriteType::Block3, SpriteType::Block4,
+        SpriteType::Block5, SpriteType::Block6, SpriteType::Block7, SpriteType::Block8,
+    ];
+
     pub fn digit(digit: u32) -> Self {
         match digit {
             0 => SpriteType::Digit0,
@@ -67,19 +92,18 @@ impl SpriteType {
             6 => SpriteType::Block6,
             7 => SpriteType::Block7,
             8 => SpriteType::Block8,
-            _ => {
-                panic!("digit out of bounds");
-            }
+            _ => SpriteType::BlockNumber(digit.try_into().unwrap_or(u8::MAX)),
         }
     }
 }
 
 impl SpriteType {
-    const SPRITESHEET_WIDTH: f32 = 139.;
-    const SPRITESHEET_HEIGHT: f32 = 84.;
+    pub(crate) const SPRITESHEET_WIDTH: f32 = 153.;
+    pub(crate) const SPRITESHEET_HEIGHT: f32 = 84.;
 
+    /// Where the sprite is on the classic spritesheet, as x, y, width and height.
     #[rustfmt::skip]
-    fn pixels(&self) -> (u32, u32, u32, u32) {
+    pub(crate) fn pixels(&self) -> (u32, u32, u32, u32) {
         match self {
             SpriteType::Digit1 => (0,   0, 13, 23),
             SpriteType::Digit2 => (14,  0, 13, 23),
@@ -91,6 +115,7 @@ impl SpriteType {
             SpriteType::Digit8 => (98,  0, 13, 23),
             SpriteType::Digit9 => (112, 0, 13, 23),
             SpriteType::Digit0 => (126, 0, 13, 23),
+            SpriteType::DigitMinus => (140, 0, 13, 23),
 
             SpriteType::FaceSmileyUp        => (0,   24, 26, 26),
             SpriteType::FaceSmileyDown      => (27,  24, 26, 26),
@@ -115,91 +140,207 @@ impl SpriteType {
             SpriteType::Block6 => (85,  68, 16, 16),
             SpriteType::Block7 => (102, 68, 16, 16),
             SpriteType::Block8 => (119, 68, 16, 16),
+            SpriteType::BlockNumber(_) => SpriteType::BlockEmptyDown.pixels(),
         }
     }
 
-    fn rect(&self) -> Rect {
-        let (x, y, w, h) = self.pixels();
-        [
-            pos2(
-                x as f32 / Self::SPRITESHEET_WIDTH,
-                y as f32 / Self::SPRITESHEET_HEIGHT,
-            ),
-            pos2(
-                (x + w) as f32 / Self::SPRITESHEET_WIDTH,
-                (y + h) as f32 / Self::SPRITESHEET_HEIGHT,
-            ),
-        ]
-        .into()
+    /// The sprite to take from a spritesheet, which has no counts above 8.
+    fn on_sheet(self) -> SpriteType {
+        match self {
+            SpriteType::BlockNumber(_) => SpriteType::BlockEmptyDown,
+            sprite => sprite,
+        }
     }
 
-    fn size(&self) -> Vec2 {
+    /// Size at a zoom of 1, the same for every theme so the layout doesn't change.
+    pub(crate) fn size(&self) -> Vec2 {
         let (_x, _y, w, h) = self.pixels();
         vec2(w as f32, h as f32)
     }
 }
 
+/// How sprites are drawn.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Renderer {
+    /// From the theme's spritesheet.
+    #[default]
+    Spritesheet,
+    /// With shapes and text, see `VectorStyle`.
+    Vector,
+}
+
+impl Renderer {
+    pub const ALL: [Renderer; 2] = [Renderer::Spritesheet, Renderer::Vector];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Renderer::Spritesheet => "Sprites",
+            Renderer::Vector => "Vector",
+        }
+    }
+}
+
 pub struct Sprites {
-    spritesheet: ImageSource<'static>,
+    pub renderer: Renderer,
+    pub vector: VectorStyle,
+    theme: Theme,
+    /// For sprites the theme doesn't have.
+    classic: Theme,
 }
 
 impl Default for Sprites {
     fn default() -> Self {
         Sprites {
-            spritesheet: egui::include_image!("../assets/minesweeper_spritesheet.png"),
+            renderer: Renderer::default(),
+            vector: VectorStyle::default(),
+            theme: Theme::classic(),
+            classic: Theme::classic(),
         }
     }
 }
 
 impl Sprites {
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
     pub fn button(&self, ui: &mut Ui, sprite: SpriteType, zoom: f32) -> Response {
-        let image = self.image_helper(sprite, zoom);
-        let button = Button::image(image).frame(false);
+        match self.renderer {
+            Renderer::Spritesheet => {
+                let image = self.image_helper(sprite, zoom);
+                let button = Button::image(image).frame(false);
 
-        ui.spacing_mut().button_padding = vec2(0.0, 0.0);
-        ui.add(button)
+                ui.spacing_mut().button_padding = vec2(0.0, 0.0);
+                ui.add(button)
+            }
+            Renderer::Vector => {
+                let (rect, response) = ui.allocate_exact_size(sprite.size() * zoom, Sense::click());
+                self.vector.paint(ui.painter(), sprite, rect);
+                response
+            }
+        }
     }
 
     pub fn image(&self, ui: &mut Ui, sprite: SpriteType, zoom: f32) -> Response {
-        let image = self.image_helper(sprite, zoom);
-        ui.add(image)
+        let (rect, response) = ui.allocate_exact_size(sprite.size() * zoom, Sense::hover());
+        self.paint(ui, sprite, rect);
+        response
+    }
+
+    /// Draw a sprite stretched over a rect, without taking up any space.
+    pub fn paint(&self, ui: &Ui, sprite: SpriteType, rect: Rect) {
+        match self.renderer {
+            Renderer::Spritesheet => {
+                self.image_helper(sprite, 1.0).paint_at(ui, rect);
+                if let SpriteType::BlockNumber(count) = sprite {
+                    self.vector.paint_count(ui.painter(), count, rect);
+                }
+            }
+            Renderer::Vector => self.vector.paint(ui.painter(), sprite, rect),
+        }
+    }
+
+    /// Draw many sprites, each stretched over its rect.
+    ///
+    /// From a spritesheet, they are drawn as one mesh per sheet, which is much faster than one
+    /// image each.
+    pub fn paint_all(&self, ui: &Ui, sprites: &[(SpriteType, Rect)]) {
+        let textures = (
+            self.texture(ui, &self.theme),
+            self.texture(ui, &self.classic),
+        );
+        let (Renderer::Spritesheet, (Some(theme_texture), Some(classic_texture))) =
+            (self.renderer, textures)
+        else {
+            // vector sprites, or a spritesheet that's still loading
+            for &(sprite, rect) in sprites {
+                self.paint(ui, sprite, rect);
+            }
+            return;
+        };
+
+        let mut theme_mesh = Mesh::with_texture(theme_texture);
+        let mut classic_mesh = Mesh::with_texture(classic_texture);
+        for &(sprite, rect) in sprites {
+            let sprite = sprite.on_sheet();
+            match self.theme.uv(sprite) {
+                Some(uv) => theme_mesh.add_rect_with_uv(rect, uv, Color32::WHITE),
+                None => {
+                    let uv = self.classic.uv(sprite).unwrap();
+                    classic_mesh.add_rect_with_uv(rect, uv, Color32::WHITE);
+                }
+            }
+        }
+        let painter = ui.painter();
+        painter.add(classic_mesh);
+        painter.add(theme_mesh);
+
+        for &(sprite, rect) in sprites {
+            if let SpriteType::BlockNumber(count) = sprite {
+                self.vector.paint_count(painter, count, rect);
+            }
+        }
     }
 
+    /// The texture of a theme's spritesheet, once it has loaded.
+    fn texture(&self, ui: &Ui, theme: &Theme) -> Option<TextureId> {
+        let image = Image::new(theme.image().clone()).texture_options(TextureOptions::NEAREST);
+        match image.load_for_size(ui.ctx(), Vec2::splat(1.0)) {
+            Ok(TexturePoll::Ready { texture }) => Some(texture.id),
+            _ => None,
+        }
+    }
+
+    /// Scaled with nearest-neighbor sampling, so pixel art stays crisp at any zoom.
     fn image_helper(&self, sprite: SpriteType, zoom: f32) -> Image<'_> {
         let size = sprite.size() * zoom;
-        let rect = sprite.rect();
+        let sprite = sprite.on_sheet();
+        let (theme, rect) = match self.theme.uv(sprite) {
+            Some(rect) => (&self.theme, rect),
+            None => (&self.classic, self.classic.uv(sprite).unwrap()),
+        };
 
-        Image::new(self.spritesheet.clone())
+        Image::new(theme.image().clone())
             .maintain_aspect_ratio(false)
             .fit_to_exact_size(size)
             .uv(rect)
+            .texture_options(TextureOptions::NEAREST)
     }
 
-    pub fn digit(&self, ui: &mut Ui, digit: u32, zoom: f32) {
-        let sprite = SpriteType::digit(digit);
-        self.image(ui, sprite, zoom);
-    }
+    /// Draw a number as an LED display `width` digits wide, with leading zeros, and a minus sign
+    /// in the leftmost digit for negative values. Values that don't fit show as the nearest value
+    /// that does, such as 999 or -99 for a display 3 digits wide.
+    pub fn digits(&self, ui: &mut Ui, value: i64, width: u32, dir: Direction, zoom: f32) {
+        let width = width.max(1);
+        let max = 10i64.pow(width) - 1;
+        let min = -(10i64.pow(width - 1) - 1);
+        let value = value.clamp(min, max);
 
-    pub fn digits(&self, ui: &mut Ui, value: u32, dir: Direction, zoom: f32) {
-        if value > 999 {
-            panic!("value out of bounds - must be 0-999");
+        // least significant digit first
+        let mut sprites: Vec<SpriteType> = (0..width)
+            .map(|place| {
+                let digit = value.unsigned_abs() / 10u64.pow(place) % 10;
+                SpriteType::digit(digit as u32)
+            })
+            .collect();
+        if value < 0 {
+            sprites[width as usize - 1] = SpriteType::DigitMinus;
         }
 
-        let d1 = value % 10;
-        let value = value / 10;
-        let d2 = value % 10;
-        let d3 = value / 10;
-
         match dir {
             Direction::LeftToRight | Direction::TopDown => {
-                self.digit(ui, d3, zoom);
-                self.digit(ui, d2, zoom);
-                self.digit(ui, d1, zoom);
+                for sprite in sprites.into_iter().rev() {
+                    self.image(ui, sprite, zoom);
+                }
             }
             Direction::RightToLeft | Direction::BottomUp => {
-                self.digit(ui, d1, zoom);
-                self.digit(ui, d2, zoom);
-                self.digit(ui, d3, zoom);
+                for sprite in sprites {
+                    self.image(ui, sprite, zoom);
+                }
             }
         }
     }
@@ -0,0 +1,350 @@
+use crate::game::GameConfig;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// File the results of finished games are added to, in the game's data folder.
+pub const STATS_FILE: &str = "stats.txt";
+
+/// File the best times are added to, in the game's data folder.
+pub const SCORES_FILE: &str = "scores.txt";
+
+/// Number of best times kept for each board.
+pub const TOP_SCORES: usize = 10;
+
+/// One finished game.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct GameRecord {
+    pub config: GameConfig,
+    pub won: bool,
+    /// Time on the clock when the game ended, including any hint penalty.
+    pub time: Duration,
+    pub bbbv: usize,
+    /// Hints the player asked for.
+    pub hints: usize,
+    /// When the game ended, in seconds since the Unix epoch.
+    pub date: u64,
+}
+
+impl GameRecord {
+    /// A game that has just ended.
+    pub fn now(config: GameConfig, won: bool, time: Duration, bbbv: usize, hints: usize) -> Self {
+        let date = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        GameRecord {
+            config,
+            won,
+            time,
+            bbbv,
+            hints,
+            date,
+        }
+    }
+
+    /// One line of the statistics file: date, width, height, mines, `won` or `lost`, time in
+    /// milliseconds, 3BV and hints.
+    fn to_line(self) -> String {
+        format!(
+            "{} {} {} {} {} {} {} {}",
+            self.date,
+            self.config.width(),
+            self.config.height(),
+            self.config.mines(),
+            if self.won { "won" } else { "lost" },
+            self.time.as_millis(),
+            self.bbbv,
+            self.hints
+        )
+    }
+
+    fn from_line(line: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let [date, width, height, mines, result, millis, bbbv, hints] = fields[..] else {
+            return Err(
+                "expected date, width, height, mines, result, time, 3BV and hints".to_string(),
+            );
+        };
+        let number = |field: &str| {
+            field
+                .parse::<u64>()
+                .map_err(|_| format!("invalid number: {field}"))
+        };
+        let won = match result {
+            "won" => true,
+            "lost" => false,
+            _ => return Err(format!("invalid result: {result}")),
+        };
+        Ok(GameRecord {
+            config: GameConfig::new(
+                number(width)? as u32,
+                number(height)? as u32,
+                number(mines)? as usize,
+            ),
+            won,
+            time: Duration::from_millis(number(millis)?),
+            bbbv: number(bbbv)? as usize,
+            hints: number(hints)? as usize,
+            date: number(date)?,
+        })
+    }
+}
+
+/// Totals for one `GameConfig`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConfigStats {
+    pub played: usize,
+    pub won: usize,
+    /// Wins since the last loss.
+    pub current_streak: usize,
+    pub best_streak: usize,
+    pub best_time: Option<Duration>,
+    pub average_time: Option<Duration>,
+}
+
+impl ConfigStats {
+    pub fn win_rate(&self) -> f64 {
+        if self.played == 0 {
+            0.0
+        } else {
+            self.won as f64 / self.played as f64
+        }
+    }
+}
+
+/// Every finished game, kept in a file one line per game so each result is saved as it happens.
+///
+/// Lines starting with `#` are comments, and lines that can't be read are skipped, so one bad
+/// line doesn't lose the rest of the history.
+pub struct Statistics {
+    path: PathBuf,
+    records: Vec<GameRecord>,
+}
+
+impl Statistics {
+    /// Read the games recorded in a file, starting afresh if there isn't one yet, along with why
+    /// each skipped line couldn't be read.
+    pub fn load(path: impl AsRef<Path>) -> Result<(Self, Vec<String>), String> {
+        let path = path.as_ref().to_path_buf();
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(format!("{}: {err}", path.display())),
+        };
+
+        let mut records = Vec::new();
+        let mut skipped = Vec::new();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match GameRecord::from_line(line) {
+                Ok(record) => records.push(record),
+                Err(err) => skipped.push(format!("{} line {}: {err}", path.display(), n + 1)),
+            }
+        }
+        Ok((Statistics { path, records }, skipped))
+    }
+
+    /// No games, saved to a file once the first one is added.
+    pub fn empty(path: impl AsRef<Path>) -> Self {
+        Statistics {
+            path: path.as_ref().to_path_buf(),
+            records: Vec::new(),
+        }
+    }
+
+    /// Record a game, adding it to the end of the file.
+    pub fn add(&mut self, record: GameRecord) -> Result<(), String> {
+        self.records.push(record);
+        let error = |err: std::io::Error| format!("{}: {err}", self.path.display());
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(error)?;
+        writeln!(file, "{}", record.to_line()).map_err(error)
+    }
+
+    pub fn records(&self) -> &[GameRecord] {
+        &self.records
+    }
+
+    /// Every config with a recorded game, in the order they were first played.
+    pub fn configs(&self) -> Vec<GameConfig> {
+        let mut configs = Vec::new();
+        for record in &self.records {
+            if !configs.contains(&record.config) {
+                configs.push(record.config);
+            }
+        }
+        configs
+    }
+
+    pub fn for_config(&self, config: GameConfig) -> ConfigStats {
+        let mut stats = ConfigStats::default();
+        let mut total_time = Duration::ZERO;
+        for record in self.records.iter().filter(|r| r.config == config) {
+            stats.played += 1;
+            if record.won {
+                stats.won += 1;
+                stats.current_streak += 1;
+                stats.best_streak = stats.best_streak.max(stats.current_streak);
+                stats.best_time = Some(stats.best_time.map_or(record.time, |t| t.min(record.time)));
+                total_time += record.time;
+            } else {
+                stats.current_streak = 0;
+            }
+        }
+        if stats.won > 0 {
+            stats.average_time = Some(total_time / stats.won as u32);
+        }
+        stats
+    }
+}
+
+/// A winning time, with the name of the player.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HighScore {
+    pub config: GameConfig,
+    pub time: Duration,
+    pub name: String,
+    /// When the game was won, in seconds since the Unix epoch.
+    pub date: u64,
+}
+
+impl HighScore {
+    /// One line of the scores file: date, width, height, mines, time in milliseconds, and the
+    /// name, which may have spaces.
+    fn to_line(&self) -> String {
+        format!(
+            "{} {} {} {} {} {}",
+            self.date,
+            self.config.width(),
+            self.config.height(),
+            self.config.mines(),
+            self.time.as_millis(),
+            self.name
+        )
+    }
+
+    fn from_line(line: &str) -> Result<Self, String> {
+        let mut fields = line.splitn(6, ' ');
+        let mut number = || {
+            let field = fields
+                .next()
+                .ok_or("expected date, width, height, mines, time and name")?;
+            field
+                .parse::<u64>()
+                .map_err(|_| format!("invalid number: {field}"))
+        };
+        let (date, width, height, mines, millis) =
+            (number()?, number()?, number()?, number()?, number()?);
+        Ok(HighScore {
+            config: GameConfig::new(width as u32, height as u32, mines as usize),
+            time: Duration::from_millis(millis),
+            name: fields.next().unwrap_or_default().trim().to_string(),
+            date,
+        })
+    }
+}
+
+/// The `TOP_SCORES` best times on each board, kept in a file one line per time.
+///
+/// Lines starting with `#` are comments, and lines that can't be read are skipped.
+pub struct HighScores {
+    path: PathBuf,
+    scores: Vec<HighScore>,
+}
+
+impl HighScores {
+    /// Read the times recorded in a file, starting afresh if there isn't one yet, along with why
+    /// each skipped line couldn't be read.
+    pub fn load(path: impl AsRef<Path>) -> Result<(Self, Vec<String>), String> {
+        let path = path.as_ref().to_path_buf();
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(format!("{}: {err}", path.display())),
+        };
+
+        let mut scores = Vec::new();
+        let mut skipped = Vec::new();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match HighScore::from_line(line) {
+                Ok(score) => scores.push(score),
+                Err(err) => skipped.push(format!("{} line {}: {err}", path.display(), n + 1)),
+            }
+        }
+        Ok((HighScores { path, scores }, skipped))
+    }
+
+    /// No times, saved to a file once the first one is added.
+    pub fn empty(path: impl AsRef<Path>) -> Self {
+        HighScores {
+            path: path.as_ref().to_path_buf(),
+            scores: Vec::new(),
+        }
+    }
+
+    /// The best times on a board, fastest first, with earlier times ahead of equal later ones.
+    pub fn table(&self, config: GameConfig) -> Vec<&HighScore> {
+        let mut table: Vec<&HighScore> =
+            self.scores.iter().filter(|s| s.config == config).collect();
+        table.sort_by_key(|s| (s.time, s.date));
+        table.truncate(TOP_SCORES);
+        table
+    }
+
+    /// Whether a winning time on a board makes its table.
+    pub fn qualifies(&self, config: GameConfig, time: Duration) -> bool {
+        let table = self.table(config);
+        table.len() < TOP_SCORES || table.last().is_some_and(|s| time < s.time)
+    }
+
+    /// Record a time, adding it to the end of the file.
+    pub fn add(&mut self, score: HighScore) -> Result<(), String> {
+        let line = score.to_line();
+        self.scores.push(score);
+        let error = |err: std::io::Error| format!("{}: {err}", self.path.display());
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(error)?;
+        writeln!(file, "{line}").map_err(error)
+    }
+
+    /// Every board with a recorded time, in the order they were first played.
+    pub fn configs(&self) -> Vec<GameConfig> {
+        let mut configs = Vec::new();
+        for score in &self.scores {
+            if !configs.contains(&score.config) {
+                configs.push(score.config);
+            }
+        }
+        configs
+    }
+}
+
+/// A date in seconds since the Unix epoch, as year-month-day in UTC.
+pub fn format_date(secs: u64) -> String {
+    // days to civil date, after Howard Hinnant's algorithm
+    let days = (secs / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
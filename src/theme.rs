@@ -0,0 +1,169 @@
+use crate::sprites::SpriteType;
+use egui::{ImageSource, Rect, Vec2, pos2, vec2};
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+/// Folder searched for themes, each in a folder of its own.
+pub const THEMES_DIR: &str = "themes";
+
+/// File in each theme folder describing where its sprites are.
+pub const MANIFEST: &str = "theme.txt";
+
+/// Name of the built-in theme, which a theme folder can't take.
+pub const CLASSIC: &str = "Classic";
+
+/// A spritesheet, and where each sprite is on it.
+///
+/// A theme folder holds an image and a `theme.txt` manifest. The manifest names the image on an
+/// `image FILE` line, then gives one sprite per line as its `SpriteType` name and the x, y,
+/// width and height of its rectangle in pixels. Lines starting with `#` are comments:
+///
+/// ```text
+/// image sheet.png
+/// FaceSmileyUp 0 24 26 26
+/// BlockEmptyUp 0 51 16 16
+/// ```
+pub struct Theme {
+    name: String,
+    image: ImageSource<'static>,
+    image_size: Vec2,
+    pixels: HashMap<SpriteType, (u32, u32, u32, u32)>,
+}
+
+impl Theme {
+    /// The spritesheet built into the game.
+    pub fn classic() -> Self {
+        Theme {
+            name: CLASSIC.to_string(),
+            image: egui::include_image!("../assets/minesweeper_spritesheet.png"),
+            image_size: vec2(
+                SpriteType::SPRITESHEET_WIDTH,
+                SpriteType::SPRITESHEET_HEIGHT,
+            ),
+            pixels: SpriteType::ALL.iter().map(|&s| (s, s.pixels())).collect(),
+        }
+    }
+
+    /// Load the theme in a folder, named after the folder.
+    ///
+    /// Sprites missing from the manifest are allowed, see `missing`, but a sprite outside the
+    /// image or a line that can't be read is an error.
+    pub fn load(dir: impl AsRef<Path>) -> Result<Self, String> {
+        let dir = dir.as_ref();
+        let name = dir
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| dir.display().to_string());
+
+        let manifest_path = dir.join(MANIFEST);
+        let manifest = std::fs::read_to_string(&manifest_path)
+            .map_err(|err| format!("{}: {err}", manifest_path.display()))?;
+
+        let mut image_file = None;
+        let mut pixels = HashMap::<SpriteType, (u32, u32, u32, u32)>::new();
+        for (n, line) in manifest.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |message: &str| format!("{MANIFEST} line {}: {message}", n + 1);
+
+            if let Some(file) = line.strip_prefix("image ") {
+                image_file = Some(file.trim().to_string());
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [sprite, x, y, w, h] = fields[..] else {
+                return Err(error("expected a sprite name, x, y, width and height"));
+            };
+            let sprite = SpriteType::ALL
+                .into_iter()
+                .find(|s| format!("{s:?}") == sprite)
+                .ok_or_else(|| error(&format!("unknown sprite: {sprite}")))?;
+            let mut rect = [0; 4];
+            for (value, field) in rect.iter_mut().zip([x, y, w, h]) {
+                *value = field
+                    .parse()
+                    .map_err(|_| error(&format!("invalid number: {field}")))?;
+            }
+            let [x, y, w, h] = rect;
+            if pixels.insert(sprite, (x, y, w, h)).is_some() {
+                return Err(error(&format!("{sprite:?} is given twice")));
+            }
+        }
+
+        let image_file = image_file.ok_or(format!("{MANIFEST}: missing image line"))?;
+        let image_path = dir.join(&image_file);
+        let bytes =
+            std::fs::read(&image_path).map_err(|err| format!("{}: {err}", image_path.display()))?;
+        let (width, height) = image::ImageReader::new(Cursor::new(&bytes))
+            .with_guessed_format()
+            .map_err(|err| err.to_string())
+            .and_then(|reader| reader.into_dimensions().map_err(|err| err.to_string()))
+            .map_err(|err| format!("{}: {err}", image_path.display()))?;
+
+        for (sprite, &(x, y, w, h)) in &pixels {
+            if x.checked_add(w).is_none_or(|right| right > width)
+                || y.checked_add(h).is_none_or(|bottom| bottom > height)
+            {
+                return Err(format!(
+                    "{MANIFEST}: {sprite:?} is outside the {width}x{height} image"
+                ));
+            }
+        }
+
+        Ok(Theme {
+            image: ImageSource::Bytes {
+                uri: format!("bytes://themes/{name}/{image_file}").into(),
+                bytes: bytes.into(),
+            },
+            name,
+            image_size: vec2(width as f32, height as f32),
+            pixels,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub(crate) fn image(&self) -> &ImageSource<'static> {
+        &self.image
+    }
+
+    /// Sprites the manifest leaves out, which are drawn from the classic theme instead.
+    pub fn missing(&self) -> Vec<SpriteType> {
+        SpriteType::ALL
+            .into_iter()
+            .filter(|s| !self.pixels.contains_key(s))
+            .collect()
+    }
+
+    /// Where a sprite is on the image, in texture coordinates.
+    pub(crate) fn uv(&self, sprite: SpriteType) -> Option<Rect> {
+        let &(x, y, w, h) = self.pixels.get(&sprite)?;
+        let size = self.image_size;
+        Some(Rect::from_min_max(
+            pos2(x as f32 / size.x, y as f32 / size.y),
+            pos2((x + w) as f32 / size.x, (y + h) as f32 / size.y),
+        ))
+    }
+}
+
+/// Folders in `THEMES_DIR` with a manifest, sorted by name. A folder named after the built-in
+/// theme is left out, as it couldn't be told apart from it.
+pub fn find_themes() -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(THEMES_DIR) else {
+        return Vec::new();
+    };
+    let mut themes: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.join(MANIFEST).is_file())
+        .filter(|path| path.file_name().is_none_or(|name| name != CLASSIC))
+        .collect();
+    themes.sort();
+    themes
+}
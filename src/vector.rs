@@ -0,0 +1,329 @@
+use crate::sprites::SpriteType;
+use egui::{Align2, Color32, FontId, Painter, Pos2, Rect, Shape, Stroke, Vec2, vec2};
+use std::f32::consts::PI;
+
+const FACE: Color32 = Color32::from_rgb(255, 255, 0);
+const GRAY: Color32 = Color32::from_rgb(192, 192, 192);
+const SHADOW: Color32 = Color32::from_rgb(128, 128, 128);
+const LED_ON: Color32 = Color32::from_rgb(255, 0, 0);
+const LED_OFF: Color32 = Color32::from_rgb(72, 0, 0);
+
+/// Classic colors of the counts, from 1 to 8.
+pub const CLASSIC_NUMBER_COLORS: [Color32; 8] = [
+    Color32::from_rgb(0, 0, 255),
+    Color32::from_rgb(0, 128, 0),
+    Color32::from_rgb(255, 0, 0),
+    Color32::from_rgb(0, 0, 128),
+    Color32::from_rgb(128, 0, 0),
+    Color32::from_rgb(0, 128, 128),
+    Color32::from_rgb(0, 0, 0),
+    Color32::from_rgb(128, 128, 128),
+];
+
+/// Draws sprites with shapes and text instead of a spritesheet, so they stay sharp at any zoom.
+pub struct VectorStyle {
+    /// Color of each count, starting from 1. Counts beyond the end start over from the first.
+    pub number_colors: Vec<Color32>,
+}
+
+impl Default for VectorStyle {
+    fn default() -> Self {
+        VectorStyle {
+            number_colors: CLASSIC_NUMBER_COLORS.to_vec(),
+        }
+    }
+}
+
+impl VectorStyle {
+    pub fn number_color(&self, count: u8) -> Color32 {
+        if self.number_colors.is_empty() || count == 0 {
+            return Color32::BLACK;
+        }
+        self.number_colors[(count as usize - 1) % self.number_colors.len()]
+    }
+
+    /// Draw a sprite to fill a rect.
+    pub fn paint(&self, painter: &Painter, sprite: SpriteType, rect: Rect) {
+        let size = sprite.size();
+        // place points in the sprite's own pixels, so shapes line up with the spritesheet
+        let canvas = Canvas {
+            painter,
+            rect,
+            scale: vec2(rect.width() / size.x, rect.height() / size.y),
+        };
+
+        match sprite {
+            SpriteType::Digit0 => canvas.led(0b0111111),
+            SpriteType::Digit1 => canvas.led(0b0000110),
+            SpriteType::Digit2 => canvas.led(0b1011011),
+            SpriteType::Digit3 => canvas.led(0b1001111),
+            SpriteType::Digit4 => canvas.led(0b1100110),
+            SpriteType::Digit5 => canvas.led(0b1101101),
+            SpriteType::Digit6 => canvas.led(0b1111101),
+            SpriteType::Digit7 => canvas.led(0b0000111),
+            SpriteType::Digit8 => canvas.led(0b1111111),
+            SpriteType::Digit9 => canvas.led(0b1101111),
+            SpriteType::DigitMinus => canvas.led(0b1000000),
+
+            SpriteType::FaceSmileyUp => canvas.face(true, Expression::Smile),
+            SpriteType::FaceSmileyDown => canvas.face(false, Expression::Smile),
+            SpriteType::FaceRuhRoh => canvas.face(true, Expression::RuhRoh),
+            SpriteType::FaceCool => canvas.face(true, Expression::Cool),
+            SpriteType::FaceXXX => canvas.face(true, Expression::Dead),
+
+            SpriteType::BlockEmptyUp => canvas.raised(),
+            SpriteType::BlockEmptyDown => canvas.sunken(GRAY),
+            SpriteType::BlockFlag => {
+                canvas.raised();
+                canvas.flag();
+            }
+            SpriteType::BlockQuestionUp => {
+                canvas.raised();
+                canvas.text("?", Color32::BLACK);
+            }
+            SpriteType::BlockQuestionDown => {
+                canvas.sunken(GRAY);
+                canvas.text("?", Color32::BLACK);
+            }
+            SpriteType::BlockMine => {
+                canvas.sunken(GRAY);
+                canvas.mine();
+            }
+            SpriteType::BlockMineRed => {
+                canvas.sunken(Color32::RED);
+                canvas.mine();
+            }
+            SpriteType::BlockMineX => {
+                canvas.sunken(GRAY);
+                canvas.mine();
+                let stroke = canvas.stroke(1.5, Color32::RED);
+                canvas.line(3.0, 3.0, 13.0, 13.0, stroke);
+                canvas.line(13.0, 3.0, 3.0, 13.0, stroke);
+            }
+
+            SpriteType::Block1 => self.number(&canvas, 1),
+            SpriteType::Block2 => self.number(&canvas, 2),
+            SpriteType::Block3 => self.number(&canvas, 3),
+            SpriteType::Block4 => self.number(&canvas, 4),
+            SpriteType::Block5 => self.number(&canvas, 5),
+            SpriteType::Block6 => self.number(&canvas, 6),
+            SpriteType::Block7 => self.number(&canvas, 7),
+            SpriteType::Block8 => self.number(&canvas, 8),
+            SpriteType::BlockNumber(count) => self.number(&canvas, count),
+        }
+    }
+
+    /// Draw just the count of a revealed block, over whatever is already there.
+    pub fn paint_count(&self, painter: &Painter, count: u8, rect: Rect) {
+        let size = SpriteType::BlockEmptyDown.size();
+        let canvas = Canvas {
+            painter,
+            rect,
+            scale: vec2(rect.width() / size.x, rect.height() / size.y),
+        };
+        canvas.text(&count.to_string(), self.number_color(count));
+    }
+
+    fn number(&self, canvas: &Canvas<'_>, count: u8) {
+        canvas.sunken(GRAY);
+        canvas.text(&count.to_string(), self.number_color(count));
+    }
+}
+
+enum Expression {
+    Smile,
+    RuhRoh,
+    Cool,
+    Dead,
+}
+
+/// A painter that takes positions in sprite pixels.
+struct Canvas<'a> {
+    painter: &'a Painter,
+    rect: Rect,
+    scale: Vec2,
+}
+
+impl Canvas<'_> {
+    fn pos(&self, x: f32, y: f32) -> Pos2 {
+        self.rect.min + vec2(x * self.scale.x, y * self.scale.y)
+    }
+
+    fn stroke(&self, width: f32, color: Color32) -> Stroke {
+        Stroke::new(width * self.scale.min_elem(), color)
+    }
+
+    fn fill(&self, x0: f32, y0: f32, x1: f32, y1: f32, color: Color32) {
+        let rect = Rect::from_min_max(self.pos(x0, y0), self.pos(x1, y1));
+        self.painter.rect_filled(rect, 0.0, color);
+    }
+
+    fn line(&self, x0: f32, y0: f32, x1: f32, y1: f32, stroke: Stroke) {
+        self.painter
+            .line_segment([self.pos(x0, y0), self.pos(x1, y1)], stroke);
+    }
+
+    fn circle(&self, x: f32, y: f32, radius: f32, fill: Color32, stroke: Stroke) {
+        let radius = radius * self.scale.min_elem();
+        self.painter.circle(self.pos(x, y), radius, fill, stroke);
+    }
+
+    /// An arc around a center, with angles in degrees clockwise from the right.
+    fn arc(&self, x: f32, y: f32, radius: f32, from: f32, to: f32, stroke: Stroke) {
+        let points = (0..=12)
+            .map(|i| {
+                let angle = (from + (to - from) * i as f32 / 12.0) * PI / 180.0;
+                self.pos(x + radius * angle.cos(), y + radius * angle.sin())
+            })
+            .collect();
+        self.painter.add(Shape::line(points, stroke));
+    }
+
+    /// Centered text, drawn twice a little apart to look bold like the classic counts.
+    fn text(&self, text: &str, color: Color32) {
+        let size = self.rect.height() * if text.len() > 1 { 0.55 } else { 0.75 };
+        let center = self.rect.center();
+        let offset = vec2(0.5 * self.scale.x, 0.0);
+        for pos in [center, center + offset] {
+            self.painter.text(
+                pos,
+                Align2::CENTER_CENTER,
+                text,
+                FontId::monospace(size),
+                color,
+            );
+        }
+    }
+
+    /// A raised button, lit from the top left.
+    fn raised(&self) {
+        let (w, h) = (
+            self.rect.width() / self.scale.x,
+            self.rect.height() / self.scale.y,
+        );
+        let border = (w.min(h) / 8.0).max(1.0);
+        self.fill(0.0, 0.0, w, h, SHADOW);
+        self.fill(0.0, 0.0, w - border, h - border, Color32::WHITE);
+        self.fill(border, border, w - border, h - border, GRAY);
+    }
+
+    /// A flat, pressed-in block with a thin shadow along its top and left.
+    fn sunken(&self, color: Color32) {
+        let (w, h) = (
+            self.rect.width() / self.scale.x,
+            self.rect.height() / self.scale.y,
+        );
+        self.fill(0.0, 0.0, w, h, SHADOW);
+        self.fill(1.0, 1.0, w, h, color);
+    }
+
+    fn mine(&self) {
+        let stroke = self.stroke(1.0, Color32::BLACK);
+        self.line(8.0, 2.5, 8.0, 13.5, stroke);
+        self.line(2.5, 8.0, 13.5, 8.0, stroke);
+        self.line(4.5, 4.5, 11.5, 11.5, stroke);
+        self.line(11.5, 4.5, 4.5, 11.5, stroke);
+        self.circle(8.0, 8.0, 4.0, Color32::BLACK, Stroke::NONE);
+        self.fill(6.0, 6.0, 7.5, 7.5, Color32::WHITE);
+    }
+
+    fn flag(&self) {
+        let pole = self.stroke(1.0, Color32::BLACK);
+        self.line(9.0, 3.5, 9.0, 11.5, pole);
+        self.painter.add(Shape::convex_polygon(
+            vec![self.pos(9.5, 3.0), self.pos(9.5, 8.0), self.pos(4.5, 5.5)],
+            Color32::RED,
+            Stroke::NONE,
+        ));
+        self.fill(7.0, 11.0, 11.0, 12.0, Color32::BLACK);
+        self.fill(5.0, 12.0, 13.0, 13.5, Color32::BLACK);
+    }
+
+    /// A seven-segment digit, with bits for segments a to g, from the top clockwise and then
+    /// the middle.
+    fn led(&self, segments: u8) {
+        self.fill(0.0, 0.0, 13.0, 23.0, Color32::BLACK);
+
+        // each segment as a hexagon along its length, in the middle of its width
+        let t = 1.2;
+        let horizontal = |y: f32| {
+            [
+                (2.0, y),
+                (3.0, y - t),
+                (10.0, y - t),
+                (11.0, y),
+                (10.0, y + t),
+                (3.0, y + t),
+            ]
+        };
+        let vertical = |x: f32, y0: f32, y1: f32| {
+            [
+                (x, y0),
+                (x + t, y0 + 1.0),
+                (x + t, y1 - 1.0),
+                (x, y1),
+                (x - t, y1 - 1.0),
+                (x - t, y0 + 1.0),
+            ]
+        };
+        let shapes = [
+            horizontal(2.0),
+            vertical(11.0, 2.5, 11.0),
+            vertical(11.0, 12.0, 20.5),
+            horizontal(21.0),
+            vertical(2.0, 12.0, 20.5),
+            vertical(2.0, 2.5, 11.0),
+            horizontal(11.5),
+        ];
+
+        for (i, points) in shapes.into_iter().enumerate() {
+            let color = if segments & (1 << i) != 0 {
+                LED_ON
+            } else {
+                LED_OFF
+            };
+            let points = points.iter().map(|&(x, y)| self.pos(x, y)).collect();
+            self.painter
+                .add(Shape::convex_polygon(points, color, Stroke::NONE));
+        }
+    }
+
+    fn face(&self, up: bool, expression: Expression) {
+        if up {
+            self.raised();
+        } else {
+            self.fill(0.0, 0.0, 26.0, 26.0, SHADOW);
+            self.fill(1.0, 1.0, 26.0, 26.0, GRAY);
+        }
+        // a pressed face moves down and right, like the button under it
+        let (cx, cy) = if up { (13.0, 13.0) } else { (14.0, 14.0) };
+        let black = self.stroke(1.0, Color32::BLACK);
+        self.circle(cx, cy, 8.5, FACE, black);
+
+        match expression {
+            Expression::Smile => {
+                self.circle(cx - 3.0, cy - 2.0, 1.2, Color32::BLACK, Stroke::NONE);
+                self.circle(cx + 3.0, cy - 2.0, 1.2, Color32::BLACK, Stroke::NONE);
+                self.arc(cx, cy, 4.5, 30.0, 150.0, black);
+            }
+            Expression::RuhRoh => {
+                self.circle(cx - 3.0, cy - 2.5, 1.6, Color32::BLACK, Stroke::NONE);
+                self.circle(cx + 3.0, cy - 2.5, 1.6, Color32::BLACK, Stroke::NONE);
+                self.circle(cx, cy + 4.0, 2.0, FACE, black);
+            }
+            Expression::Cool => {
+                self.line(cx - 7.5, cy - 3.0, cx + 7.5, cy - 3.0, black);
+                self.fill(cx - 5.0, cy - 3.0, cx - 0.5, cy, Color32::BLACK);
+                self.fill(cx + 0.5, cy - 3.0, cx + 5.0, cy, Color32::BLACK);
+                self.arc(cx, cy, 4.5, 30.0, 150.0, black);
+            }
+            Expression::Dead => {
+                for x in [cx - 3.0, cx + 3.0] {
+                    self.line(x - 1.5, cy - 3.5, x + 1.5, cy - 0.5, black);
+                    self.line(x + 1.5, cy - 3.5, x - 1.5, cy - 0.5, black);
+                }
+                self.arc(cx, cy + 7.5, 4.5, 220.0, 320.0, black);
+            }
+        }
+    }
+}